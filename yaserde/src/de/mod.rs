@@ -1,36 +1,66 @@
+use std::collections::{HashMap, VecDeque};
 use std::io::Read;
+use xml::attribute::OwnedAttribute;
+use xml::common::{Position, TextPosition};
 use xml::name::OwnedName;
+use xml::namespace::Namespace;
 use xml::reader::{EventReader, ParserConfig, XmlEvent};
+use Error;
 use YaDeserialize;
+use XSI_NAMESPACE;
 
-pub fn from_str<T: YaDeserialize>(s: &str) -> Result<T, String> {
+pub fn from_str<T: YaDeserialize>(s: &str) -> Result<T, Error> {
   from_reader(s.as_bytes())
 }
 
-pub fn from_reader<R: Read, T: YaDeserialize>(reader: R) -> Result<T, String> {
-  T::deserialize(&mut Deserializer::new_from_reader(reader))
+pub fn from_reader<R: Read, T: YaDeserialize>(reader: R) -> Result<T, Error> {
+  let mut deserializer = Deserializer::new_from_reader(reader);
+  T::deserialize(&mut deserializer).map_err(|error| deserializer.locate(error))
 }
 
 pub struct Deserializer<R: Read> {
   depth: usize,
   reader: EventReader<R>,
   peeked: Option<XmlEvent>,
+  /// Events read before the input, standing for an attribute or text value or
+  /// replayed for a flattened field, with whether their text may be trimmed.
+  injected: VecDeque<(XmlEvent, bool)>,
+  peeked_position: TextPosition,
+  position: TextPosition,
+  /// Name and 1-based sibling index of every element opened from the root.
+  path: Vec<(String, usize)>,
+  /// Occurrences of each child name, one map per open element plus the document.
+  siblings: Vec<HashMap<String, usize>>,
+  /// Depths of the elements replayed for flattened fields, left out of the path.
+  replayed: Vec<usize>,
+  replay_pending: bool,
+  /// Depths of the elements whose text is read untrimmed, with whether the text
+  /// of their descendants is as well.
+  preserved: Vec<(usize, bool)>,
   is_map_value: bool,
 }
 
-impl<'de, R: Read> Deserializer<R> {
+impl<R: Read> Deserializer<R> {
   pub fn new(reader: EventReader<R>) -> Self {
     Deserializer {
       depth: 0,
       reader,
       peeked: None,
+      injected: VecDeque::new(),
+      peeked_position: TextPosition::new(),
+      position: TextPosition::new(),
+      path: vec![],
+      siblings: vec![HashMap::new()],
+      replayed: vec![],
+      replay_pending: false,
+      preserved: vec![],
       is_map_value: false,
     }
   }
 
   pub fn new_from_reader(reader: R) -> Self {
+    // text is trimmed as it is taken, unless its whitespace is preserved
     let config = ParserConfig::new()
-      .trim_whitespace(true)
       .whitespace_to_characters(true)
       .cdata_to_characters(true)
       .ignore_comments(true)
@@ -39,19 +69,22 @@ impl<'de, R: Read> Deserializer<R> {
     Self::new(EventReader::new_with_config(reader, config))
   }
 
-  pub fn peek(&mut self) -> Result<&XmlEvent, String> {
+  pub fn peek(&mut self) -> Result<&XmlEvent, Error> {
     if self.peeked.is_none() {
-      self.peeked = Some(self.inner_next()?);
+      let position = self.position;
+      self.peeked = Some(self.take_event()?);
+      self.peeked_position = self.position;
+      self.position = position;
     }
 
     if let Some(ref next) = self.peeked {
       Ok(next)
     } else {
-      Err(String::from("unable to peek next item"))
+      Err(Error::custom("unable to peek next item"))
     }
   }
 
-  pub fn inner_next(&mut self) -> Result<XmlEvent, String> {
+  pub fn inner_next(&mut self) -> Result<XmlEvent, Error> {
     loop {
       match self.reader.next() {
         Ok(next) => {
@@ -62,24 +95,35 @@ impl<'de, R: Read> Deserializer<R> {
             other => return Ok(other),
           }
         }
-        Err(msg) => {
-          return Err(msg.msg().to_string());
+        Err(error) => {
+          return Err(Error::Syntax(error));
         }
       }
     }
   }
 
-  pub fn next_event(&mut self) -> Result<XmlEvent, String> {
-    let next_event = if let Some(peeked) = self.peeked.take() {
-      peeked
-    } else {
-      self.inner_next()?
-    };
+  pub fn next_event(&mut self) -> Result<XmlEvent, Error> {
+    let next_event = self.take_event()?;
     match next_event {
-      XmlEvent::StartElement { .. } => {
+      XmlEvent::StartElement { ref name, .. } => {
         self.depth += 1;
+        if self.replay_pending {
+          self.replay_pending = false;
+          self.replayed.push(self.depth);
+        } else {
+          self.push_path(&name.local_name);
+        }
       }
       XmlEvent::EndElement { .. } => {
+        while self.preserved.last().map(|&(depth, _)| depth) == Some(self.depth) {
+          self.preserved.pop();
+        }
+        if self.replayed.last() == Some(&self.depth) {
+          self.replayed.pop();
+        } else {
+          self.path.pop();
+          self.siblings.pop();
+        }
         self.depth -= 1;
       }
       _ => {}
@@ -88,6 +132,352 @@ impl<'de, R: Read> Deserializer<R> {
     Ok(next_event)
   }
 
+  /// Take the next event, with its text trimmed unless its whitespace is
+  /// preserved. Text left empty is skipped.
+  fn take_event(&mut self) -> Result<XmlEvent, Error> {
+    if let Some(peeked) = self.peeked.take() {
+      self.position = self.peeked_position;
+      return Ok(peeked);
+    }
+
+    loop {
+      let (event, trim) = self.take_raw_event()?;
+      match event {
+        XmlEvent::Characters(ref text) if trim && !self.preserves_whitespace() => {
+          let trimmed = text.trim_matches(is_xml_whitespace);
+          if !trimmed.is_empty() {
+            return Ok(XmlEvent::Characters(trimmed.to_string()));
+          }
+        }
+        event => return Ok(event),
+      }
+    }
+  }
+
+  fn take_raw_event(&mut self) -> Result<(XmlEvent, bool), Error> {
+    if let Some(injected) = self.injected.pop_front() {
+      Ok(injected)
+    } else {
+      let event = self.inner_next()?;
+      self.position = self.reader.position();
+      Ok((event, true))
+    }
+  }
+
+  fn preserves_whitespace(&self) -> bool {
+    let depth = self.depth;
+    self
+      .preserved
+      .iter()
+      .any(|&(preserved, descendants)| descendants || preserved == depth)
+  }
+
+  /// Keep the whitespace of the text read up to the end of the element whose
+  /// start was just read, for mixed content where it is significant.
+  pub fn preserve_whitespace(&mut self) {
+    self.preserved.push((self.depth, true));
+  }
+
+  /// Keep the whitespace of the text directly inside the element whose start
+  /// was just read, so a flattened field replaying it can preserve it.
+  pub fn preserve_text_whitespace(&mut self) {
+    self.preserved.push((self.depth, false));
+  }
+
+  /// Number of elements opened and not yet closed by `next_event`.
+  pub fn depth(&self) -> usize {
+    self.depth
+  }
+
+  /// Position of the last event returned by `next_event`.
+  pub fn position(&self) -> TextPosition {
+    self.position
+  }
+
+  /// Path from the root to the current element, like `/library/book[3]/price`.
+  pub fn path(&self) -> String {
+    if self.path.is_empty() {
+      return String::from("/");
+    }
+
+    self
+      .path
+      .iter()
+      .map(|&(ref name, index)| path_segment(name, index))
+      .collect()
+  }
+
+  /// Attach the position and element path of the offending event to an error.
+  ///
+  /// A peeked but not yet consumed event is the one being inspected, so it is
+  /// preferred over the last consumed one.
+  pub fn locate(&self, error: Error) -> Error {
+    let position = match error {
+      Error::Located { .. } => return error,
+      Error::Syntax(ref error) => error.position(),
+      _ if self.peeked.is_some() => self.peeked_position,
+      _ => self.position,
+    };
+
+    let mut path = self.path();
+    if let Some(XmlEvent::StartElement { ref name, .. }) = self.peeked {
+      let index = self
+        .siblings
+        .last()
+        .and_then(|siblings| siblings.get(&name.local_name))
+        .map_or(1, |count| count + 1);
+      if self.path.is_empty() {
+        path.clear();
+      }
+      path += &path_segment(&name.local_name, index);
+    }
+
+    Error::Located {
+      position,
+      path,
+      error: Box::new(error),
+    }
+  }
+
+  fn push_path(&mut self, name: &str) {
+    let index = {
+      let siblings = self
+        .siblings
+        .last_mut()
+        .expect("the document level is never popped");
+      let count = siblings.entry(name.to_string()).or_insert(0);
+      *count += 1;
+      *count
+    };
+    self.path.push((name.to_string(), index));
+    self.siblings.push(HashMap::new());
+  }
+
+  /// Deserialize an attribute value through `T::deserialize`, as if it were an
+  /// element named `@name` containing the value.
+  pub fn deserialize_attribute<T: YaDeserialize>(
+    &mut self,
+    attribute: &OwnedAttribute,
+  ) -> Result<T, Error> {
+    self.deserialize_attribute_with(attribute, T::deserialize)
+  }
+
+  /// Deserialize an attribute value through `deserialize`, like
+  /// `deserialize_attribute`.
+  pub fn deserialize_attribute_with<T, F>(
+    &mut self,
+    attribute: &OwnedAttribute,
+    deserialize: F,
+  ) -> Result<T, Error>
+  where
+    F: FnOnce(&mut Self) -> Result<T, Error>,
+  {
+    let name = format!("@{}", attribute.name.local_name);
+    self.deserialize_value(name, &attribute.value, deserialize)
+  }
+
+  /// Deserialize text content through `T::deserialize`, as if it were an
+  /// element named `text()` containing the text.
+  pub fn deserialize_text<T: YaDeserialize>(&mut self, text: &str) -> Result<T, Error> {
+    self.deserialize_text_with(text, T::deserialize)
+  }
+
+  /// Deserialize text content through `deserialize`, like `deserialize_text`.
+  pub fn deserialize_text_with<T, F>(&mut self, text: &str, deserialize: F) -> Result<T, Error>
+  where
+    F: FnOnce(&mut Self) -> Result<T, Error>,
+  {
+    self.deserialize_value(String::from("text()"), text, deserialize)
+  }
+
+  /// Deserialize the items of an `xs:list` attribute value, separated by
+  /// whitespace or by `separator`, each like `deserialize_attribute_with`.
+  pub fn deserialize_attribute_list_with<T, F>(
+    &mut self,
+    attribute: &OwnedAttribute,
+    separator: Option<&str>,
+    deserialize: F,
+  ) -> Result<Vec<T>, Error>
+  where
+    F: FnMut(&mut Self) -> Result<T, Error>,
+  {
+    let name = format!("@{}", attribute.name.local_name);
+    self.deserialize_list(&name, &attribute.value, separator, deserialize)
+  }
+
+  /// Deserialize the items of an `xs:list` text content, like
+  /// `deserialize_attribute_list_with`.
+  pub fn deserialize_text_list_with<T, F>(
+    &mut self,
+    text: &str,
+    separator: Option<&str>,
+    deserialize: F,
+  ) -> Result<Vec<T>, Error>
+  where
+    F: FnMut(&mut Self) -> Result<T, Error>,
+  {
+    self.deserialize_list("text()", text, separator, deserialize)
+  }
+
+  /// Deserialize the items of the `xs:list` text of the next element, like
+  /// `deserialize_attribute_list_with`.
+  pub fn deserialize_element_list_with<T, F>(
+    &mut self,
+    separator: Option<&str>,
+    deserialize: F,
+  ) -> Result<Vec<T>, Error>
+  where
+    F: FnMut(&mut Self) -> Result<T, Error>,
+  {
+    let name = match self.next_event()? {
+      XmlEvent::StartElement { name, .. } => name,
+      event => return Err(Error::UnexpectedEvent(format!("{:?}", event))),
+    };
+    let text = self.read_characters()?;
+    let items = self.deserialize_text_list_with(&text, separator, deserialize)?;
+    self.expect_end_element(&name)?;
+    Ok(items)
+  }
+
+  fn deserialize_list<T, F>(
+    &mut self,
+    name: &str,
+    text: &str,
+    separator: Option<&str>,
+    mut deserialize: F,
+  ) -> Result<Vec<T>, Error>
+  where
+    F: FnMut(&mut Self) -> Result<T, Error>,
+  {
+    let items: Vec<&str> = match separator {
+      _ if text.trim().is_empty() => vec![],
+      Some(separator) => text.split(separator).map(str::trim).collect(),
+      None => text.split_whitespace().collect(),
+    };
+
+    items
+      .into_iter()
+      .map(|item| self.deserialize_value(name.to_string(), item, &mut deserialize))
+      .collect()
+  }
+
+  fn deserialize_value<T, F>(
+    &mut self,
+    name: String,
+    text: &str,
+    deserialize: F,
+  ) -> Result<T, Error>
+  where
+    F: FnOnce(&mut Self) -> Result<T, Error>,
+  {
+    if let Some(peeked) = self.peeked.take() {
+      self.injected.push_front((peeked, false));
+    }
+
+    let name = OwnedName::local(name);
+    self
+      .injected
+      .push_front((XmlEvent::EndElement { name: name.clone() }, false));
+    if !text.is_empty() {
+      self
+        .injected
+        .push_front((XmlEvent::Characters(text.to_string()), false));
+    }
+    let start = XmlEvent::StartElement {
+      name,
+      attributes: vec![],
+      namespace: Namespace::empty(),
+    };
+    self.injected.push_front((start, false));
+
+    deserialize(self)
+  }
+
+  /// Whether the element at the reader position is marked with `xsi:nil="true"`.
+  pub fn is_nil_element(&mut self) -> Result<bool, Error> {
+    Ok(match *self.peek()? {
+      XmlEvent::StartElement { ref attributes, .. } => attributes.iter().any(|attribute| {
+        attribute.name.local_name == "nil"
+          && attribute.name.namespace.as_deref() == Some(XSI_NAMESPACE)
+          && attribute.value == "true"
+      }),
+      _ => false,
+    })
+  }
+
+  /// Consume the element at the reader position through its end element,
+  /// with all its children. Any other event is consumed alone.
+  pub fn skip_element(&mut self) -> Result<(), Error> {
+    let depth = self.depth;
+    if let XmlEvent::StartElement { .. } = self.next_event()? {
+      loop {
+        match self.next_event()? {
+          XmlEvent::EndElement { .. } if self.depth == depth => return Ok(()),
+          event @ XmlEvent::EndDocument => {
+            return Err(Error::UnexpectedEvent(format!("{:?}", event)))
+          }
+          _ => {}
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Take the events of the element at the reader position through its end
+  /// element, without deserializing them, to replay them later with
+  /// `deserialize_flattened`. Their text is kept untrimmed until it is replayed.
+  pub fn collect_element(&mut self) -> Result<Vec<XmlEvent>, Error> {
+    let mut events = vec![];
+    let mut depth = 0;
+    loop {
+      let event = match self.peeked.take() {
+        Some(peeked) => {
+          self.position = self.peeked_position;
+          peeked
+        }
+        None => self.take_raw_event()?.0,
+      };
+      match event {
+        XmlEvent::StartElement { .. } => depth += 1,
+        XmlEvent::EndElement { .. } => depth -= 1,
+        XmlEvent::EndDocument => return Err(Error::UnexpectedEvent(format!("{:?}", event))),
+        _ => {}
+      }
+      events.push(event);
+      if depth == 0 {
+        return Ok(events);
+      }
+    }
+  }
+
+  /// Deserialize a flattened field through `T::deserialize`, from an element
+  /// opened by `start` and holding `content`. The element is replayed before
+  /// the rest of the input and left out of the path.
+  pub fn deserialize_flattened<T: YaDeserialize>(
+    &mut self,
+    start: &XmlEvent,
+    content: &[XmlEvent],
+  ) -> Result<T, Error> {
+    let name = match *start {
+      XmlEvent::StartElement { ref name, .. } => name.clone(),
+      ref event => return Err(Error::UnexpectedEvent(format!("{:?}", event))),
+    };
+
+    if let Some(peeked) = self.peeked.take() {
+      self.injected.push_front((peeked, false));
+    }
+    self
+      .injected
+      .push_front((XmlEvent::EndElement { name }, false));
+    for event in content.iter().rev() {
+      self.injected.push_front((event.clone(), true));
+    }
+    self.injected.push_front((start.clone(), false));
+    self.replay_pending = true;
+
+    T::deserialize(self)
+  }
+
   pub fn set_map_value(&mut self) {
     self.is_map_value = true;
   }
@@ -96,35 +486,98 @@ impl<'de, R: Read> Deserializer<R> {
     ::std::mem::replace(&mut self.is_map_value, false)
   }
 
-  pub fn read_inner_value<T, F: FnOnce(&mut Self) -> Result<T, String>>(
+  pub fn read_inner_value<T, F: FnOnce(&mut Self) -> Result<T, Error>>(
     &mut self,
     f: F,
-  ) -> Result<T, String> {
+  ) -> Result<T, Error> {
     if self.unset_map_value() {
       if let Ok(XmlEvent::StartElement { name, .. }) = self.next_event() {
         let result = f(self)?;
         self.expect_end_element(&name)?;
         Ok(result)
       } else {
-        Err("Internal error: Bad Event".to_string())
+        Err(Error::custom("Internal error: Bad Event"))
       }
     } else {
       f(self)
     }
   }
 
-  pub fn expect_end_element(&mut self, start_name: &OwnedName) -> Result<(), String> {
-    if let XmlEvent::EndElement { name, .. } = self.next_event()? {
-      if name == *start_name {
-        Ok(())
-      } else {
-        Err(format!(
-          "End tag </{}> didn't match the start tag <{}>",
-          name.local_name, start_name.local_name
-        ))
-      }
+  /// Read the text of the next element, or the pending characters when already
+  /// inside an element, and convert it with `parse`. An element without content
+  /// yields an empty string. The conversion runs before the element is closed so
+  /// its errors point inside it.
+  pub fn read_text<T, F>(&mut self, parse: F) -> Result<T, Error>
+  where
+    F: FnOnce(String) -> Result<T, Error>,
+  {
+    let start_name = if let XmlEvent::StartElement { ref name, .. } = *self.peek()? {
+      Some(name.clone())
     } else {
-      Err(format!("Unexpected token </{}>", start_name.local_name))
+      None
+    };
+
+    match start_name {
+      Some(name) => {
+        self.next_event()?;
+        let value = parse(self.read_characters()?)?;
+        self.expect_end_element(&name)?;
+        Ok(value)
+      }
+      None => parse(self.read_characters()?),
+    }
+  }
+
+  fn read_characters(&mut self) -> Result<String, Error> {
+    if let XmlEvent::Characters(_) = *self.peek()? {
+      if let XmlEvent::Characters(text) = self.next_event()? {
+        return Ok(text);
+      }
+    }
+    Ok(String::new())
+  }
+
+  pub fn expect_end_element(&mut self, start_name: &OwnedName) -> Result<(), Error> {
+    match self.next_event()? {
+      XmlEvent::EndElement { name } => {
+        if name == *start_name {
+          Ok(())
+        } else {
+          Err(Error::UnexpectedElement {
+            expected: format!("/{}", start_name.local_name),
+            found: format!("/{}", name.local_name),
+          })
+        }
+      }
+      XmlEvent::StartElement { name, .. } => Err(Error::UnexpectedElement {
+        expected: format!("/{}", start_name.local_name),
+        found: name.local_name,
+      }),
+      event => Err(Error::UnexpectedEvent(format!(
+        "{:?}, expected </{}>",
+        event, start_name.local_name
+      ))),
     }
   }
 }
+
+fn path_segment(name: &str, index: usize) -> String {
+  if index > 1 {
+    format!("/{}[{}]", name, index)
+  } else {
+    format!("/{}", name)
+  }
+}
+
+/// Whether `events` are only whitespace text, like the text left between the
+/// children replayed to flattened fields.
+pub fn is_blank(events: &[XmlEvent]) -> bool {
+  events.iter().all(|event| match *event {
+    XmlEvent::Characters(ref text) => text.chars().all(is_xml_whitespace),
+    _ => false,
+  })
+}
+
+fn is_xml_whitespace(c: char) -> bool {
+  c == ' ' || c == '\t' || c == '\n' || c == '\r'
+}
@@ -0,0 +1,193 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use xml;
+use xml::common::{Position, TextPosition};
+
+/// Error returned by every serialization and deserialization entry point.
+#[derive(Debug)]
+pub enum Error {
+  /// The input is not well-formed XML or could not be read.
+  Syntax(xml::reader::Error),
+  /// The underlying writer failed.
+  Io(io::Error),
+  /// The XML emitter refused an event, e.g. an unbalanced end element.
+  Emitter(xml::writer::Error),
+  /// An element was found where another one was expected.
+  UnexpectedElement { expected: String, found: String },
+  /// An event that cannot appear at this point of the document.
+  UnexpectedEvent(String),
+  /// A required element is absent.
+  MissingElement(String),
+  /// A required attribute is absent.
+  MissingAttribute(String),
+  /// A child element matches no field of a `deny_unknown_fields` container.
+  UnknownElement(String),
+  /// An attribute matches no field of a `deny_unknown_fields` container.
+  UnknownAttribute(String),
+  /// A value matches none of the variants of an enum.
+  UnknownVariant {
+    found: String,
+    expected: &'static [&'static str],
+  },
+  /// A text or attribute value cannot be converted into the expected type.
+  Conversion {
+    value: String,
+    target: &'static str,
+    source: Box<dyn StdError + Send + Sync>,
+  },
+  /// A deserialized value was rejected by the `TryFrom` conversion into the expected type.
+  Invalid {
+    target: &'static str,
+    source: Box<dyn StdError + Send + Sync>,
+  },
+  /// An element is not bound to the expected namespace.
+  Namespace { expected: String },
+  /// Any other error, mostly raised by hand-written implementations.
+  Custom(String),
+  /// A deserialization error with the location of the offending event.
+  Located {
+    position: TextPosition,
+    path: String,
+    error: Box<Error>,
+  },
+}
+
+impl Error {
+  /// Build an error from a free-form message.
+  pub fn custom<T: fmt::Display>(msg: T) -> Self {
+    Error::Custom(msg.to_string())
+  }
+
+  /// Build a conversion error for `value` that could not be parsed as `target`.
+  pub fn conversion<E>(value: &str, target: &'static str, source: E) -> Self
+  where
+    E: Into<Box<dyn StdError + Send + Sync>>,
+  {
+    Error::Conversion {
+      value: value.to_string(),
+      target,
+      source: source.into(),
+    }
+  }
+
+  /// Position in the input document where the error was raised, when known.
+  pub fn position(&self) -> Option<TextPosition> {
+    match *self {
+      Error::Located { position, .. } => Some(position),
+      Error::Syntax(ref error) => Some(error.position()),
+      _ => None,
+    }
+  }
+
+  /// Path of the element being deserialized when the error was raised, when known.
+  pub fn path(&self) -> Option<&str> {
+    match *self {
+      Error::Located { ref path, .. } => Some(path),
+      _ => None,
+    }
+  }
+
+  /// The error without its location.
+  pub fn inner(&self) -> &Error {
+    match *self {
+      Error::Located { ref error, .. } => error.inner(),
+      ref error => error,
+    }
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Error::Syntax(ref error) => write!(f, "{}", error),
+      Error::Io(ref error) => write!(f, "{}", error),
+      Error::Emitter(ref error) => write!(f, "{}", error),
+      Error::UnexpectedElement {
+        ref expected,
+        ref found,
+      } => write!(f, "unexpected element <{}>, expected <{}>", found, expected),
+      Error::UnexpectedEvent(ref event) => write!(f, "unexpected event {}", event),
+      Error::MissingElement(ref name) => write!(f, "missing element <{}>", name),
+      Error::MissingAttribute(ref name) => write!(f, "missing attribute {:?}", name),
+      Error::UnknownElement(ref name) => write!(f, "unknown element <{}>", name),
+      Error::UnknownAttribute(ref name) => write!(f, "unknown attribute {:?}", name),
+      Error::UnknownVariant {
+        ref found,
+        expected,
+      } => {
+        write!(f, "unknown variant '{}', expected one of [", found)?;
+        for (index, variant) in expected.iter().enumerate() {
+          if index > 0 {
+            f.write_str(", ")?;
+          }
+          write!(f, "'{}'", variant)?;
+        }
+        f.write_str("]")
+      }
+      Error::Conversion {
+        ref value,
+        target,
+        ref source,
+      } => write!(f, "unable to parse {:?} as {}: {}", value, target, source),
+      Error::Invalid { target, ref source } => write!(f, "invalid {}: {}", target, source),
+      Error::Namespace { ref expected } => write!(f, "bad namespace, expected {}", expected),
+      Error::Custom(ref msg) => f.write_str(msg),
+      Error::Located {
+        position,
+        ref path,
+        ref error,
+      } => match **error {
+        // syntax errors already report their position
+        Error::Syntax(_) => write!(f, "{} at {}", error, path),
+        _ => write!(f, "{} at {} ({})", error, path, position),
+      },
+    }
+  }
+}
+
+impl StdError for Error {
+  fn source(&self) -> Option<&(dyn StdError + 'static)> {
+    match *self {
+      Error::Syntax(ref error) => Some(error),
+      Error::Io(ref error) => Some(error),
+      Error::Emitter(ref error) => Some(error),
+      Error::Conversion { ref source, .. } => Some(&**source),
+      Error::Invalid { ref source, .. } => Some(&**source),
+      Error::Located { ref error, .. } => Some(&**error),
+      _ => None,
+    }
+  }
+}
+
+impl From<xml::reader::Error> for Error {
+  fn from(error: xml::reader::Error) -> Self {
+    Error::Syntax(error)
+  }
+}
+
+impl From<xml::writer::Error> for Error {
+  fn from(error: xml::writer::Error) -> Self {
+    match error {
+      xml::writer::Error::Io(error) => Error::Io(error),
+      error => Error::Emitter(error),
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(error: io::Error) -> Self {
+    Error::Io(error)
+  }
+}
+
+#[test]
+fn error_source() {
+  let error = Error::conversion("abc", "u8", "abc".parse::<u8>().unwrap_err());
+  assert_eq!(
+    error.to_string(),
+    "unable to parse \"abc\" as u8: invalid digit found in string"
+  );
+  assert!(error.source().is_some());
+  assert!(Error::custom("message").source().is_none());
+}
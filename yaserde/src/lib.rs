@@ -6,81 +6,94 @@ extern crate xml;
 #[macro_use]
 extern crate yaserde_derive;
 
+use std::borrow::Cow;
 use std::io::{Read, Write};
-use xml::writer::XmlEvent;
+use std::rc::Rc;
+use std::sync::Arc;
 
 pub mod de;
+mod error;
+mod map;
+mod sequence;
 pub mod ser;
 
+pub use error::Error;
+
+/// Namespace of the `xsi:nil` attribute marking `None` items of sequences.
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
 pub trait YaDeserialize: Sized {
-  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, String>;
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error>;
 }
 
 pub trait YaSerialize: Sized {
-  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), String>;
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error>;
+
+  /// Add the attributes of the value to the start element being built, used
+  /// when the value is flattened into the enclosing element.
+  fn serialize_attributes<W: Write>(&self, _writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    Ok(())
+  }
 }
 
 pub trait Visitor<'de>: Sized {
   /// The value produced by this visitor.
   type Value;
 
-  fn visit_bool(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected bool {:?}", v))
+  fn visit_bool(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected bool {:?}", v)))
   }
 
-  fn visit_i8(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected i8 {:?}", v))
+  fn visit_i8(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected i8 {:?}", v)))
   }
 
-  fn visit_u8(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected u8 {:?}", v))
+  fn visit_u8(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected u8 {:?}", v)))
   }
 
-  fn visit_i16(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected i16 {:?}", v))
+  fn visit_i16(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected i16 {:?}", v)))
   }
 
-  fn visit_u16(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected u16 {:?}", v))
+  fn visit_u16(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected u16 {:?}", v)))
   }
 
-  fn visit_i32(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected i32 {:?}", v))
+  fn visit_i32(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected i32 {:?}", v)))
   }
 
-  fn visit_u32(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected u32 {:?}", v))
+  fn visit_u32(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected u32 {:?}", v)))
   }
 
-  fn visit_i64(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected i64 {:?}", v))
+  fn visit_i64(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected i64 {:?}", v)))
   }
 
-  fn visit_u64(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected u64 {:?}", v))
+  fn visit_u64(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected u64 {:?}", v)))
   }
 
-  fn visit_f32(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected f32 {:?}", v))
+  fn visit_f32(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected f32 {:?}", v)))
   }
 
-  fn visit_f64(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected f64 {:?}", v))
+  fn visit_f64(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected f64 {:?}", v)))
   }
 
-  fn visit_str(self, v: &str) -> Result<Self::Value, String> {
-    Err(format!("Unexpected str {:?}", v))
+  fn visit_str(self, v: &str) -> Result<Self::Value, Error> {
+    Err(Error::custom(format!("Unexpected str {:?}", v)))
   }
 }
 
 macro_rules! serialize_type {
   ($type:ty) => {
     impl YaSerialize for $type {
-      fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), String> {
-        let content = format!("{}", self);
-        let event = XmlEvent::characters(&content);
-        let _ret = writer.write(event);
-        Ok(())
+      fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+        writer.write_text_value(&format!("{}", self))
       }
     }
   };
@@ -88,6 +101,7 @@ macro_rules! serialize_type {
 
 serialize_type!(bool);
 serialize_type!(char);
+serialize_type!(String);
 
 serialize_type!(usize);
 serialize_type!(u8);
@@ -104,6 +118,79 @@ serialize_type!(i64);
 serialize_type!(f32);
 serialize_type!(f64);
 
+macro_rules! deserialize_type {
+  ($type:ty) => {
+    impl YaDeserialize for $type {
+      fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+        reader.read_text(|content| {
+          content
+            .parse()
+            .map_err(|error| Error::conversion(&content, stringify!($type), error))
+        })
+      }
+    }
+  };
+}
+
+deserialize_type!(bool);
+deserialize_type!(char);
+deserialize_type!(String);
+
+deserialize_type!(usize);
+deserialize_type!(u8);
+deserialize_type!(u16);
+deserialize_type!(u32);
+deserialize_type!(u64);
+
+deserialize_type!(isize);
+deserialize_type!(i8);
+deserialize_type!(i16);
+deserialize_type!(i32);
+deserialize_type!(i64);
+
+deserialize_type!(f32);
+deserialize_type!(f64);
+
+/// Smart pointers are written as the value they point to.
+macro_rules! pointer_type {
+  ($pointer:ident) => {
+    impl<T: YaSerialize> YaSerialize for $pointer<T> {
+      fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+        (**self).serialize(writer)
+      }
+
+      fn serialize_attributes<W: Write>(
+        &self,
+        writer: &mut ser::Serializer<W>,
+      ) -> Result<(), Error> {
+        (**self).serialize_attributes(writer)
+      }
+    }
+
+    impl<T: YaDeserialize> YaDeserialize for $pointer<T> {
+      fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+        T::deserialize(reader).map($pointer::new)
+      }
+    }
+  };
+}
+
+pointer_type!(Box);
+pointer_type!(Rc);
+pointer_type!(Arc);
+
+impl<'a> YaSerialize for Cow<'a, str> {
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    writer.write_text_value(self)
+  }
+}
+
+impl<'a> YaDeserialize for Cow<'a, str> {
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    String::deserialize(reader).map(Cow::Owned)
+  }
+}
+
 #[test]
 fn default_visitor() {
   struct Test;
@@ -114,7 +201,7 @@ fn default_visitor() {
   macro_rules! test_type {
     ($visitor:tt, $message:expr) => {{
       let t = Test {};
-      assert_eq!(t.$visitor(""), Err($message.to_string()));
+      assert_eq!(t.$visitor("").unwrap_err().to_string(), $message);
     }};
   }
 
@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::io::{Read, Write};
+use xml::common::{is_name_char, is_name_start_char};
+use xml::reader;
+use xml::writer;
+use {de, ser, Error, YaDeserialize, YaSerialize};
+
+/// Write a map as an element holding one child per entry, named after the
+/// entry key and holding its value: `<map><key>value</key></map>`.
+fn serialize_map<'a, W, K, V, I>(entries: I, writer: &mut ser::Serializer<W>) -> Result<(), Error>
+where
+  W: Write,
+  K: 'a + YaSerialize,
+  V: 'a + YaSerialize,
+  I: Iterator<Item = (&'a K, &'a V)>,
+{
+  let name = writer.get_start_event_name();
+  let skip = writer.skip_start_end();
+  if let (Some(ref name), false) = (&name, skip) {
+    writer.write_start_element(writer::XmlEvent::start_element(name.as_str()))?;
+  }
+  writer.set_start_event_name(None);
+  writer.set_skip_start_end(false);
+
+  for (key, value) in entries {
+    let key = writer.capture_text(|writer| key.serialize(writer))?;
+    if !is_element_name(&key) {
+      return Err(Error::custom(format!(
+        "map key {:?} is not a valid element name, write the entries with `key_attribute`",
+        key
+      )));
+    }
+    writer.set_start_event_name(Some(key));
+    value.serialize(writer)?;
+  }
+
+  if name.is_some() && !skip {
+    writer.write(writer::XmlEvent::end_element())?;
+  }
+  Ok(())
+}
+
+/// Whether `name` is an XML name without namespace prefix.
+fn is_element_name(name: &str) -> bool {
+  let mut chars = name.chars();
+  match chars.next() {
+    Some(c) if c != ':' && is_name_start_char(c) => chars.all(|c| c != ':' && is_name_char(c)),
+    _ => false,
+  }
+}
+
+/// Read a map written by `serialize_map`, keys are deserialized from the names
+/// of the children.
+fn deserialize_map<R, K, V, M>(reader: &mut de::Deserializer<R>) -> Result<M, Error>
+where
+  R: Read,
+  K: YaDeserialize,
+  V: YaDeserialize,
+  M: Default + Extend<(K, V)>,
+{
+  let start_name = match reader.next_event()? {
+    reader::XmlEvent::StartElement { name, .. } => name,
+    event => return Err(Error::UnexpectedEvent(format!("{:?}", event))),
+  };
+
+  let mut map = M::default();
+  loop {
+    match reader.peek()?.to_owned() {
+      reader::XmlEvent::StartElement { name, .. } => {
+        let key = reader.deserialize_text(&name.local_name)?;
+        let value = V::deserialize(reader)?;
+        map.extend(Some((key, value)));
+      }
+      reader::XmlEvent::EndElement { .. } => break,
+      reader::XmlEvent::Characters(_) => {
+        reader.next_event()?;
+      }
+      event => return Err(Error::UnexpectedEvent(format!("{:?}", event))),
+    }
+  }
+  reader.expect_end_element(&start_name)?;
+  Ok(map)
+}
+
+impl<K, V, S> YaSerialize for HashMap<K, V, S>
+where
+  K: YaSerialize + Eq + Hash,
+  V: YaSerialize,
+  S: BuildHasher,
+{
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    serialize_map(self.iter(), writer)
+  }
+}
+
+impl<K, V, S> YaDeserialize for HashMap<K, V, S>
+where
+  K: YaDeserialize + Eq + Hash,
+  V: YaDeserialize,
+  S: BuildHasher + Default,
+{
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    deserialize_map(reader)
+  }
+}
+
+impl<K: YaSerialize + Ord, V: YaSerialize> YaSerialize for BTreeMap<K, V> {
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    serialize_map(self.iter(), writer)
+  }
+}
+
+impl<K: YaDeserialize + Ord, V: YaDeserialize> YaDeserialize for BTreeMap<K, V> {
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    deserialize_map(reader)
+  }
+}
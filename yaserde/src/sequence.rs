@@ -0,0 +1,106 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::io::{Read, Write};
+use xml::reader;
+use {de, ser, Error, YaDeserialize, YaSerialize};
+
+/// Write a sequence as one element per item, each named like the sequence:
+/// `<item>1</item><item>2</item>`.
+fn serialize_sequence<'a, W, T, I>(items: I, writer: &mut ser::Serializer<W>) -> Result<(), Error>
+where
+  W: Write,
+  T: 'a + YaSerialize,
+  I: Iterator<Item = &'a T>,
+{
+  let name = writer.get_start_event_name();
+  let skip = writer.skip_start_end();
+  for item in items {
+    writer.set_start_event_name(name.clone());
+    writer.set_skip_start_end(skip);
+    item.serialize(writer)?;
+  }
+
+  writer.set_start_event_name(None);
+  writer.set_skip_start_end(false);
+  Ok(())
+}
+
+/// Read a sequence written by `serialize_sequence`, from the consecutive
+/// elements named like the one at the reader position.
+fn deserialize_sequence<R, T, S>(reader: &mut de::Deserializer<R>) -> Result<S, Error>
+where
+  R: Read,
+  T: YaDeserialize,
+  S: Default + Extend<T>,
+{
+  let start_name = match reader.peek()?.to_owned() {
+    reader::XmlEvent::StartElement { name, .. } => name,
+    event => return Err(Error::UnexpectedEvent(format!("{:?}", event))),
+  };
+
+  let mut sequence = S::default();
+  loop {
+    match *reader.peek()? {
+      reader::XmlEvent::StartElement { ref name, .. } if *name == start_name => {}
+      _ => break,
+    }
+    sequence.extend(Some(T::deserialize(reader)?));
+  }
+  Ok(sequence)
+}
+
+impl<T: YaSerialize> YaSerialize for Vec<T> {
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    serialize_sequence(self.iter(), writer)
+  }
+}
+
+impl<T: YaDeserialize> YaDeserialize for Vec<T> {
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    deserialize_sequence(reader)
+  }
+}
+
+impl<T: YaSerialize> YaSerialize for VecDeque<T> {
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    serialize_sequence(self.iter(), writer)
+  }
+}
+
+impl<T: YaDeserialize> YaDeserialize for VecDeque<T> {
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    deserialize_sequence(reader)
+  }
+}
+
+impl<T, S> YaSerialize for HashSet<T, S>
+where
+  T: YaSerialize + Eq + Hash,
+  S: BuildHasher,
+{
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    serialize_sequence(self.iter(), writer)
+  }
+}
+
+impl<T, S> YaDeserialize for HashSet<T, S>
+where
+  T: YaDeserialize + Eq + Hash,
+  S: BuildHasher + Default,
+{
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    deserialize_sequence(reader)
+  }
+}
+
+impl<T: YaSerialize + Ord> YaSerialize for BTreeSet<T> {
+  fn serialize<W: Write>(&self, writer: &mut ser::Serializer<W>) -> Result<(), Error> {
+    serialize_sequence(self.iter(), writer)
+  }
+}
+
+impl<T: YaDeserialize + Ord> YaDeserialize for BTreeSet<T> {
+  fn deserialize<R: Read>(reader: &mut de::Deserializer<R>) -> Result<Self, Error> {
+    deserialize_sequence(reader)
+  }
+}
@@ -1,26 +1,27 @@
 use std::io::{Cursor, Write};
+use std::mem;
 use std::str;
-use xml;
+use xml::writer::events::StartElementBuilder;
 use xml::writer::XmlEvent;
 use xml::{EmitterConfig, EventWriter};
+use Error;
 use YaSerialize;
+use XSI_NAMESPACE;
 
-pub fn to_string<T: YaSerialize>(model: &T) -> Result<String, String> {
+pub fn to_string<T: YaSerialize>(model: &T) -> Result<String, Error> {
   let buf = Cursor::new(Vec::new());
   let cursor = serialize_with_writer(model, buf)?;
   let data = str::from_utf8(cursor.get_ref()).expect("Found invalid UTF-8");
   Ok(String::from(data))
 }
 
-pub fn serialize_with_writer<W: Write, T: YaSerialize>(model: &T, writer: W) -> Result<W, String> {
+pub fn serialize_with_writer<W: Write, T: YaSerialize>(model: &T, writer: W) -> Result<W, Error> {
   let mut serializer = Serializer::new_from_writer(writer);
-  match model.serialize(&mut serializer) {
-    Ok(()) => Ok(serializer.into_inner()),
-    Err(msg) => Err(msg),
-  }
+  model.serialize(&mut serializer)?;
+  Ok(serializer.into_inner())
 }
 
-pub fn to_string_content<T: YaSerialize>(model: &T) -> Result<String, String> {
+pub fn to_string_content<T: YaSerialize>(model: &T) -> Result<String, Error> {
   let buf = Cursor::new(Vec::new());
   let cursor = serialize_with_writer_content(model, buf)?;
   let data = str::from_utf8(cursor.get_ref()).expect("Found invalid UTF-8");
@@ -30,27 +31,30 @@ pub fn to_string_content<T: YaSerialize>(model: &T) -> Result<String, String> {
 pub fn serialize_with_writer_content<W: Write, T: YaSerialize>(
   model: &T,
   writer: W,
-) -> Result<W, String> {
+) -> Result<W, Error> {
   let mut serializer = Serializer::new_for_inner(writer);
   serializer.set_skip_start_end(true);
-  match model.serialize(&mut serializer) {
-    Ok(()) => Ok(serializer.into_inner()),
-    Err(msg) => Err(msg),
-  }
+  model.serialize(&mut serializer)?;
+  Ok(serializer.into_inner())
 }
 
 pub struct Serializer<W: Write> {
   writer: EventWriter<W>,
   skip_start_end: bool,
   start_event_name: Option<String>,
+  attributes: Vec<(String, String)>,
+  /// Text written while serializing a value in attribute position.
+  captured_text: Option<String>,
 }
 
-impl<'de, W: Write> Serializer<W> {
+impl<W: Write> Serializer<W> {
   pub fn new(writer: EventWriter<W>) -> Self {
     Serializer {
       writer,
       skip_start_end: false,
       start_event_name: None,
+      attributes: vec![],
+      captured_text: None,
     }
   }
 
@@ -86,10 +90,104 @@ impl<'de, W: Write> Serializer<W> {
     self.start_event_name = name;
   }
 
-  pub fn write<'a, E>(&mut self, event: E) -> xml::writer::Result<()>
+  pub fn write<'a, E>(&mut self, event: E) -> Result<(), Error>
   where
     E: Into<XmlEvent<'a>>,
   {
-    self.writer.write(event)
+    let event = event.into();
+    if let Some(ref mut captured_text) = self.captured_text {
+      return match event {
+        XmlEvent::Characters(text) => {
+          captured_text.push_str(text);
+          Ok(())
+        }
+        event => Err(Error::custom(format!(
+          "unable to write {:?} in a text value",
+          event
+        ))),
+      };
+    }
+
+    self.writer.write(event)?;
+    Ok(())
+  }
+
+  /// Write a leaf value as text: inside an element named by `set_start_event_name`,
+  /// or bare when no name is set or when start and end are skipped.
+  pub fn write_text_value(&mut self, text: &str) -> Result<(), Error> {
+    let name = self.start_event_name.take();
+    let skip = mem::replace(&mut self.skip_start_end, false);
+
+    match name {
+      Some(ref name) if !skip => {
+        self.write_start_element(XmlEvent::start_element(name.as_str()))?;
+        self.write(XmlEvent::characters(text))?;
+        self.write(XmlEvent::end_element())
+      }
+      _ => self.write(XmlEvent::characters(text)),
+    }
+  }
+
+  /// Write an empty element named by `set_start_event_name` and marked with
+  /// `xsi:nil="true"`, standing for a `None` item of a sequence.
+  pub fn write_nil_element(&mut self) -> Result<(), Error> {
+    let name = self.start_event_name.take().unwrap_or_default();
+    self.skip_start_end = false;
+    let element = XmlEvent::start_element(name.as_str())
+      .ns("xsi", XSI_NAMESPACE)
+      .attr("xsi:nil", "true");
+    self.write_start_element(element)?;
+    self.write(XmlEvent::end_element())
+  }
+
+  /// Run `serialize` with start and end skipped and return the text it writes
+  /// instead of emitting it, to use a value as an attribute.
+  pub fn capture_text<F>(&mut self, serialize: F) -> Result<String, Error>
+  where
+    F: FnOnce(&mut Self) -> Result<(), Error>,
+  {
+    let previous = self.captured_text.replace(String::new());
+    self.start_event_name = None;
+    self.skip_start_end = true;
+    let result = serialize(self);
+    self.skip_start_end = false;
+    let text = mem::replace(&mut self.captured_text, previous).unwrap_or_default();
+    result.map(|()| text)
+  }
+
+  /// Text of an `xs:list` value: the text of each item, captured like
+  /// `capture_text`, joined by `separator`.
+  pub fn capture_list_text<'a, T, I, F>(
+    &mut self,
+    items: I,
+    separator: &str,
+    mut serialize: F,
+  ) -> Result<String, Error>
+  where
+    T: 'a,
+    I: IntoIterator<Item = &'a T>,
+    F: FnMut(&T, &mut Self) -> Result<(), Error>,
+  {
+    let texts = items
+      .into_iter()
+      .map(|item| self.capture_text(|writer| serialize(item, writer)))
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(texts.join(separator))
+  }
+
+  /// Queue an attribute for the next start element written with `write_start_element`.
+  pub fn add_attribute<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+    self.attributes.push((name.into(), value.into()));
+  }
+
+  /// Write a start element carrying the queued attributes.
+  pub fn write_start_element(&mut self, element: StartElementBuilder) -> Result<(), Error> {
+    let attributes = mem::take(&mut self.attributes);
+    let element = attributes
+      .iter()
+      .fold(element, |element, (name, value)| {
+        element.attr(name.as_str(), value.as_str())
+      });
+    self.write(element)
   }
 }
@@ -0,0 +1,61 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn alias() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    #[yaserde(attribute, alias = "isbn10")]
+    isbn: String,
+    #[yaserde(alias = "creator", alias = "writer")]
+    author: String,
+    #[yaserde(rename = "tag", alias = "keyword")]
+    tags: Vec<String>,
+  }
+
+  let model = Book {
+    isbn: "2070612759".to_string(),
+    author: "Antoine de Saint-Exupéry".to_string(),
+    tags: vec!["tale".to_string(), "classic".to_string()],
+  };
+
+  let content = "<book isbn10=\"2070612759\"><creator>Antoine de Saint-Exupéry</creator><keyword>tale</keyword><tag>classic</tag></book>";
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let content = "<book isbn=\"2070612759\"><writer>Antoine de Saint-Exupéry</writer><tag>tale</tag><tag>classic</tag></book>";
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book isbn=\"2070612759\"><author>Antoine de Saint-Exupéry</author><tag>tale</tag><tag>classic</tag></book>";
+  assert_eq!(to_string(&model).unwrap(), content);
+}
+
+#[test]
+fn alias_tuple_struct() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book(
+    #[yaserde(attribute, rename = "isbn", alias = "isbn10")] String,
+    #[yaserde(rename = "author", alias = "creator")] String,
+  );
+
+  let model = Book(
+    "2070612759".to_string(),
+    "Antoine de Saint-Exupéry".to_string(),
+  );
+
+  let content = "<book isbn10=\"2070612759\"><creator>Antoine de Saint-Exupéry</creator></book>";
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
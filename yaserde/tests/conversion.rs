@@ -0,0 +1,90 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+#[yaserde(root = "range")]
+pub struct RangeDto {
+  #[yaserde(attribute)]
+  min: u32,
+  #[yaserde(attribute)]
+  max: u32,
+}
+
+#[test]
+fn from_into() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug, Clone)]
+  #[yaserde(from = "RangeDto", into = "RangeDto")]
+  pub struct Range {
+    bounds: (u32, u32),
+  }
+
+  impl From<RangeDto> for Range {
+    fn from(dto: RangeDto) -> Self {
+      Range {
+        bounds: (dto.min, dto.max),
+      }
+    }
+  }
+
+  impl From<Range> for RangeDto {
+    fn from(range: Range) -> Self {
+      RangeDto {
+        min: range.bounds.0,
+        max: range.bounds.1,
+      }
+    }
+  }
+
+  let model = Range { bounds: (2, 5) };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><range min=\"2\" max=\"5\" />";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Range = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn try_from() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(try_from = "RangeDto")]
+  pub struct ValidRange {
+    min: u32,
+    max: u32,
+  }
+
+  impl TryFrom<RangeDto> for ValidRange {
+    type Error = String;
+
+    fn try_from(dto: RangeDto) -> Result<Self, Self::Error> {
+      if dto.min > dto.max {
+        return Err(format!("empty range {}..{}", dto.min, dto.max));
+      }
+      Ok(ValidRange {
+        min: dto.min,
+        max: dto.max,
+      })
+    }
+  }
+
+  let loaded: ValidRange = from_str("<range min=\"2\" max=\"5\" />").unwrap();
+  assert_eq!(loaded, ValidRange { min: 2, max: 5 });
+
+  let error = from_str::<ValidRange>("<range min=\"5\" max=\"2\" />").unwrap_err();
+  assert_eq!(
+    error.inner().to_string(),
+    "invalid ValidRange: empty range 5..2"
+  );
+  let source = StdError::source(error.inner()).unwrap();
+  assert_eq!(source.to_string(), "empty range 5..2");
+}
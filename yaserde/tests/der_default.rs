@@ -7,12 +7,13 @@ extern crate yaserde_derive;
 
 use std::io::Read;
 use yaserde::de::from_str;
+use yaserde::Error;
 use yaserde::YaDeserialize;
 
 macro_rules! convert_and_validate {
   ($content: expr, $struct: tt, $model: expr) => {
-    let loaded: Result<$struct, String> = from_str($content);
-    assert_eq!(loaded, Ok($model));
+    let loaded: Result<$struct, Error> = from_str($content);
+    assert_eq!(loaded.unwrap(), $model);
   };
 }
 
@@ -95,3 +96,102 @@ fn de_default_attribute_string() {
     }
   );
 }
+
+#[test]
+fn de_default_field_trait() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "base")]
+  pub struct XmlStruct {
+    #[yaserde(default)]
+    background: String,
+  }
+
+  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base />";
+  convert_and_validate!(
+    content,
+    XmlStruct,
+    XmlStruct {
+      background: String::new(),
+    }
+  );
+}
+
+#[test]
+fn de_default_container_struct() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "base", default)]
+  pub struct XmlStruct {
+    #[yaserde(attribute)]
+    id: u32,
+    background: String,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "base", default)]
+  pub struct XmlTuple(
+    #[yaserde(attribute, rename = "id")] u32,
+    #[yaserde(rename = "background")] String,
+  );
+
+  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base />";
+  convert_and_validate!(
+    content,
+    XmlStruct,
+    XmlStruct {
+      id: 0,
+      background: String::new(),
+    }
+  );
+  convert_and_validate!(content, XmlTuple, XmlTuple(0, String::new()));
+}
+
+#[test]
+fn de_default_variant() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "base")]
+  pub struct XmlStruct {
+    background: Color,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  pub enum Color {
+    #[yaserde(default)]
+    White,
+    Black,
+  }
+
+  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><background>Grey</background></base>";
+  convert_and_validate!(
+    content,
+    XmlStruct,
+    XmlStruct {
+      background: Color::White,
+    }
+  );
+}
+
+#[test]
+fn de_default_container() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "base")]
+  pub struct XmlStruct {
+    background: Color,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug, Default)]
+  #[yaserde(default)]
+  pub enum Color {
+    White,
+    #[default]
+    Black,
+  }
+
+  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><background /></base>";
+  convert_and_validate!(
+    content,
+    XmlStruct,
+    XmlStruct {
+      background: Color::Black,
+    }
+  );
+}
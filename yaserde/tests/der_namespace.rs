@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,12 +9,13 @@ extern crate yaserde_derive;
 
 use std::io::Read;
 use yaserde::de::from_str;
+use yaserde::Error;
 use yaserde::YaDeserialize;
 
 macro_rules! convert_and_validate {
   ($content: expr, $struct: tt, $model: expr) => {
-    let loaded: Result<$struct, String> = from_str($content);
-    assert_eq!(loaded, Ok($model));
+    let loaded: Result<$struct, Error> = from_str($content);
+    assert_eq!(loaded.unwrap(), $model);
   };
 }
 
@@ -42,8 +45,14 @@ fn de_struct_namespace() {
   );
 
   let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><ns:book xmlns:ns=\"http://www.sample.com/ns/domain2\"><ns:author>Antoine de Saint-Exupéry</ns:author><ns:title>Little prince</ns:title></ns:book>";
-  let loaded: Result<Book, String> = from_str(content);
-  assert_eq!(loaded, Err("bad namespace".to_string()));
+  let error = from_str::<Book>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/book"));
+  match *error.inner() {
+    Error::Namespace { ref expected } => {
+      assert_eq!(expected, "http://www.sample.com/ns/domain");
+    }
+    ref other => panic!("unexpected error {:?}", other),
+  }
 }
 
 #[test]
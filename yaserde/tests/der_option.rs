@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls, clippy::unnecessary_cast)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,6 +9,7 @@ extern crate yaserde_derive;
 
 use std::io::Read;
 use yaserde::de::from_str;
+use yaserde::Error;
 use yaserde::YaDeserialize;
 
 macro_rules! convert_and_validate {
@@ -27,8 +30,8 @@ macro_rules! convert_and_validate {
       String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><data />")
     };
 
-    let loaded: Result<Data, String> = from_str(&source);
-    assert_eq!(loaded, Ok(model));
+    let loaded: Result<Data, Error> = from_str(&source);
+    assert_eq!(loaded.unwrap(), model);
   }};
 }
 
@@ -51,8 +54,8 @@ macro_rules! convert_and_validate_for_attribute {
       String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><data />")
     };
 
-    let loaded: Result<Data, String> = from_str(&source);
-    assert_eq!(loaded, Ok(model));
+    let loaded: Result<Data, Error> = from_str(&source);
+    assert_eq!(loaded.unwrap(), model);
   }};
 }
 
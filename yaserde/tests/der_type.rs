@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_cast)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,6 +9,7 @@ extern crate yaserde_derive;
 
 use std::io::Read;
 use yaserde::de::from_str;
+use yaserde::Error;
 use yaserde::YaDeserialize;
 
 macro_rules! convert_and_validate {
@@ -23,8 +26,8 @@ macro_rules! convert_and_validate {
       + $content
       + "</item></data>";
 
-    let loaded: Result<Data, String> = from_str(&content);
-    assert_eq!(loaded, Ok(model));
+    let loaded: Result<Data, Error> = from_str(&content);
+    assert_eq!(loaded.unwrap(), model);
   }};
 }
 
@@ -42,11 +45,34 @@ macro_rules! convert_and_validate_for_attribute {
     let content =
       String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><data item=\"") + $content + "\" />";
 
-    let loaded: Result<Data, String> = from_str(&content);
-    assert_eq!(loaded, Ok(model));
+    let loaded: Result<Data, Error> = from_str(&content);
+    assert_eq!(loaded.unwrap(), model);
   }};
 }
 
+type Text = String;
+
+mod units {
+  use std::io::Read;
+  use yaserde::de::Deserializer;
+  use yaserde::{Error, YaDeserialize};
+
+  #[derive(PartialEq, Debug)]
+  pub struct Celsius(pub f32);
+
+  impl YaDeserialize for Celsius {
+    fn deserialize<R: Read>(reader: &mut Deserializer<R>) -> Result<Self, Error> {
+      reader.read_text(|content| {
+        content
+          .trim_end_matches("°C")
+          .parse()
+          .map(Celsius)
+          .map_err(|error| Error::conversion(&content, "Celsius", error))
+      })
+    }
+  }
+}
+
 #[test]
 fn de_type() {
   convert_and_validate!(String, "test".to_string(), "test");
@@ -65,6 +91,10 @@ fn de_type() {
   convert_and_validate!(i64, -12 as i64, "-12");
   convert_and_validate!(f32, -12.5_f32 as f32, "-12.5");
   convert_and_validate!(f64, -12.5 as f64, "-12.5");
+  convert_and_validate!(std::string::String, "test".to_string(), "test");
+  convert_and_validate!(Text, "test".to_string(), "test");
+  convert_and_validate!(units::Celsius, units::Celsius(-12.5), "-12.5°C");
+  convert_and_validate!(Option<::std::string::String>, Some("test".to_string()), "test");
 
   convert_and_validate_for_attribute!(String, "test".to_string(), "test");
   convert_and_validate_for_attribute!(bool, true, "true");
@@ -82,4 +112,50 @@ fn de_type() {
   convert_and_validate_for_attribute!(i64, -12 as i64, "-12");
   convert_and_validate_for_attribute!(f32, -12.5 as f32, "-12.5");
   convert_and_validate_for_attribute!(f64, -12.5 as f64, "-12.5");
+  convert_and_validate_for_attribute!(std::string::String, "test".to_string(), "test");
+  convert_and_validate_for_attribute!(Text, "test".to_string(), "test");
+  convert_and_validate_for_attribute!(units::Celsius, units::Celsius(-12.5), "-12.5°C");
+}
+
+#[test]
+fn de_primitive() {
+  macro_rules! convert_and_validate_primitive {
+    ($type:ty, $value:expr, $content:expr) => {{
+      let content = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><value>")
+        + $content
+        + "</value>";
+
+      let loaded: Result<$type, Error> = from_str(&content);
+      assert_eq!(loaded.unwrap(), $value);
+    }};
+  }
+
+  convert_and_validate_primitive!(String, "test".to_string(), "test");
+  convert_and_validate_primitive!(String, String::new(), "");
+  convert_and_validate_primitive!(bool, true, "true");
+  convert_and_validate_primitive!(char, 'c', "c");
+  convert_and_validate_primitive!(u8, 12_u8, "12");
+  convert_and_validate_primitive!(i8, -12_i8, "-12");
+  convert_and_validate_primitive!(u16, 12_u16, "12");
+  convert_and_validate_primitive!(i16, -12_i16, "-12");
+  convert_and_validate_primitive!(u32, 12_u32, "12");
+  convert_and_validate_primitive!(i32, -12_i32, "-12");
+  convert_and_validate_primitive!(u64, 12_u64, "12");
+  convert_and_validate_primitive!(i64, -12_i64, "-12");
+  convert_and_validate_primitive!(usize, 12_usize, "12");
+  convert_and_validate_primitive!(isize, -12_isize, "-12");
+  convert_and_validate_primitive!(f32, -12.5_f32, "-12.5");
+  convert_and_validate_primitive!(f64, -12.5_f64, "-12.5");
+
+  let error = from_str::<u32>("<value>-12</value>").unwrap_err();
+  assert_eq!(error.path(), Some("/value"));
+  match *error.inner() {
+    Error::Conversion {
+      ref value, target, ..
+    } => {
+      assert_eq!(value, "-12");
+      assert_eq!(target, "u32");
+    }
+    ref other => panic!("unexpected error {:?}", other),
+  }
 }
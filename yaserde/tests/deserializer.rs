@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,12 +9,13 @@ extern crate yaserde_derive;
 
 use std::io::Read;
 use yaserde::de::from_str;
+use yaserde::Error;
 use yaserde::YaDeserialize;
 
 macro_rules! convert_and_validate {
   ($content: expr, $struct: tt, $model: expr) => {
-    let loaded: Result<$struct, String> = from_str($content);
-    assert_eq!(loaded, Ok($model));
+    let loaded: Result<$struct, Error> = from_str($content);
+    assert_eq!(loaded.unwrap(), $model);
   };
 }
 
@@ -48,6 +51,27 @@ fn de_basic() {
   );
 }
 
+#[test]
+fn de_skip_unknown_subtree() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    title: String,
+    #[yaserde(rename = "tag")]
+    tags: Vec<String>,
+  }
+
+  let content = "<book><title>Little prince</title><meta><title>x</title><tag>y</tag><meta><tag>z</tag></meta></meta><tag>tale</tag></book>";
+  convert_and_validate!(
+    content,
+    Book,
+    Book {
+      title: String::from("Little prince"),
+      tags: vec![String::from("tale")],
+    }
+  );
+}
+
 #[test]
 fn de_list_of_items() {
   #[derive(YaDeserialize, PartialEq, Debug)]
@@ -236,6 +260,7 @@ fn de_enum() {
     }
   }
 
+  #[allow(dead_code)]
   #[derive(YaDeserialize, PartialEq, Debug)]
   pub struct RGBColor {
     red: String,
@@ -273,6 +298,52 @@ fn de_enum() {
   );
 }
 
+#[test]
+fn de_complex_enum() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "drawing")]
+  pub struct Drawing {
+    shapes: Vec<Shape>,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "shape")]
+  pub enum Shape {
+    Empty,
+    #[yaserde(rename = "circle")]
+    Circle {
+      #[yaserde(attribute)]
+      r: u32,
+    },
+    #[yaserde(rename = "rect")]
+    Rect {
+      #[yaserde(attribute)]
+      w: u32,
+      #[yaserde(attribute)]
+      h: u32,
+    },
+    #[yaserde(rename = "label")]
+    Label(String),
+    #[yaserde(rename = "line")]
+    Line(#[yaserde(rename = "from")] u32, #[yaserde(rename = "to")] u32),
+  }
+
+  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><drawing><shapes><circle r=\"1\" /></shapes><shapes><rect w=\"2\" h=\"3\"/></shapes><shapes><label>A</label></shapes><shapes><line><from>4</from><to>5</to></line></shapes><shapes>Empty</shapes></drawing>";
+  convert_and_validate!(
+    content,
+    Drawing,
+    Drawing {
+      shapes: vec![
+        Shape::Circle { r: 1 },
+        Shape::Rect { w: 2, h: 3 },
+        Shape::Label("A".to_string()),
+        Shape::Line(4, 5),
+        Shape::Empty,
+      ],
+    }
+  );
+}
+
 #[test]
 fn de_attribute_enum() {
   #[derive(YaDeserialize, PartialEq, Debug)]
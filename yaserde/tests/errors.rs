@@ -5,9 +5,11 @@ extern crate yaserde;
 #[macro_use]
 extern crate yaserde_derive;
 
-use std::io::Read;
+use std::io::{self, Read, Write};
 use yaserde::de::from_str;
-use yaserde::YaDeserialize;
+use yaserde::ser::serialize_with_writer;
+use yaserde::Error;
+use yaserde::{YaDeserialize, YaSerialize};
 
 #[test]
 fn de_no_content() {
@@ -19,13 +21,14 @@ fn de_no_content() {
   }
 
   let content = "";
-  let loaded: Result<Book, String> = from_str(content);
-  assert_eq!(
-    loaded,
-    Err(String::from(
-      "Unexpected end of stream: no root element found"
-    ))
-  );
+  let error = from_str::<Book>(content).unwrap_err();
+  match *error.inner() {
+    Error::Syntax(ref error) => assert_eq!(
+      error.to_string(),
+      "1:1 Unexpected end of stream: no root element found"
+    ),
+    ref other => panic!("unexpected error {:?}", other),
+  }
 }
 
 #[test]
@@ -38,11 +41,225 @@ fn de_wrong_end_balise() {
   }
 
   let content = "<book><author>Antoine de Saint-Exupéry<title>Little prince</title></book>";
-  let loaded: Result<Book, String> = from_str(content);
+  let error = from_str::<Book>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/book/author/title"));
+  match *error.inner() {
+    Error::UnexpectedElement {
+      ref expected,
+      ref found,
+    } => {
+      assert_eq!(expected, "/author");
+      assert_eq!(found, "title");
+    }
+    ref other => panic!("unexpected error {:?}", other),
+  }
+}
+
+#[test]
+fn de_error_location() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    price: u32,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "library")]
+  pub struct Library {
+    #[yaserde(rename = "book")]
+    books: Vec<Book>,
+  }
+
+  let content = "<library>\n  <book><price>10</price></book>\n  <book><price>12</price></book>\n  <book><price>ten</price></book>\n</library>";
+  let error = from_str::<Library>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/library/book[3]/price"));
+  let position = error.position().unwrap();
+  assert_eq!((position.row, position.column), (3, 15));
+  match *error.inner() {
+    Error::Conversion {
+      ref value, target, ..
+    } => {
+      assert_eq!(value, "ten");
+      assert_eq!(target, "u32");
+    }
+    ref other => panic!("unexpected error {:?}", other),
+  }
+  assert_eq!(
+    error.to_string(),
+    "unable to parse \"ten\" as u32: invalid digit found in string at /library/book[3]/price (4:16)"
+  );
+}
+
+#[test]
+fn de_text_error_location() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "price")]
+  pub struct Price {
+    #[yaserde(attribute)]
+    currency: String,
+    #[yaserde(text)]
+    amount: u32,
+  }
+
+  let error = from_str::<Price>("<price currency=\"EUR\" />").unwrap_err();
+  assert_eq!(error.path(), Some("/price/text()"));
+}
+
+#[test]
+fn de_missing_fields() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    #[yaserde(attribute)]
+    id: u32,
+    author: String,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Entry(#[yaserde(rename = "author")] String);
+
+  let error = from_str::<Book>("<book><author>Antoine</author></book>").unwrap_err();
+  assert_eq!(
+    error.to_string(),
+    "missing attribute \"id\" at /book (1:31)"
+  );
+
+  let error = from_str::<Book>("<book id=\"1\" />").unwrap_err();
+  match *error.inner() {
+    Error::MissingElement(ref name) => assert_eq!(name, "author"),
+    ref other => panic!("unexpected error {:?}", other),
+  }
+
+  let error = from_str::<Entry>("<book />").unwrap_err();
+  match *error.inner() {
+    Error::MissingElement(ref name) => assert_eq!(name, "author"),
+    ref other => panic!("unexpected error {:?}", other),
+  }
+}
+
+#[test]
+fn de_syntax_error_location() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    author: String,
+  }
+
+  let error = from_str::<Book>("<book><author>Antoine</book>").unwrap_err();
+  assert_eq!(
+    error.to_string(),
+    "1:28 Unexpected closing tag: book != author at /book/author"
+  );
+}
+
+#[test]
+fn de_unknown_variant() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "base")]
+  pub struct XmlStruct {
+    background: Color,
+  }
+
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "color")]
+  pub enum Color {
+    White,
+    #[yaserde(rename = "black")]
+    Black,
+  }
+
+  let content = "<base><background>Blak</background></base>";
+  let error = from_str::<XmlStruct>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/base/background"));
+  assert_eq!(
+    error.inner().to_string(),
+    "unknown variant 'Blak', expected one of ['White', 'black']"
+  );
+
+  let content = "<base><background /></base>";
+  let error = from_str::<XmlStruct>(content).unwrap_err();
+  assert_eq!(
+    error.inner().to_string(),
+    "unknown variant '', expected one of ['White', 'black']"
+  );
+}
+
+#[test]
+fn se_writer_failure() {
+  struct FullDisk {
+    remaining: usize,
+  }
+
+  impl Write for FullDisk {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      if self.remaining == 0 {
+        return Err(io::Error::other("disk full"));
+      }
+      let written = buf.len().min(self.remaining);
+      self.remaining -= written;
+      Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    author: String,
+    title: String,
+  }
+
+  let model = Book {
+    author: String::from("Antoine de Saint-Exupéry"),
+    title: String::from("Little prince"),
+  };
+
+  for remaining in &[0, 10, 60, 100] {
+    let writer = FullDisk {
+      remaining: *remaining,
+    };
+    match serialize_with_writer(&model, writer) {
+      Err(Error::Io(ref error)) => assert_eq!(error.to_string(), "disk full"),
+      other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+  }
+}
+
+#[test]
+fn de_deny_unknown_fields() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "book", deny_unknown_fields)]
+  pub struct Book {
+    #[yaserde(attribute)]
+    isbn: String,
+    author: String,
+    title: String,
+  }
+
+  let content = "<book isbn=\"2070612759\"><author>Antoine de Saint-Exupéry</author><title>Little prince</title></book>";
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded.title, "Little prince");
+
+  let content = "<book isbn=\"2070612759\"><author>Antoine de Saint-Exupéry</author><titel>Little prince</titel></book>";
+  let error = from_str::<Book>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/book/titel"));
+  assert_eq!(
+    error.position().map(|position| position.to_string()),
+    Some("1:66".to_string())
+  );
+  match *error.inner() {
+    Error::UnknownElement(ref name) => assert_eq!(name, "titel"),
+    ref other => panic!("unexpected error {:?}", other),
+  }
+
+  let content = "<book isbn=\"2070612759\" lang=\"fr\"><author>Antoine de Saint-Exupéry</author><title>Little prince</title></book>";
+  let error = from_str::<Book>(content).unwrap_err();
   assert_eq!(
-    loaded,
-    Err(String::from(
-      "Unexpected closing tag: book, expected author"
-    ))
+    error.to_string(),
+    "unknown attribute \"lang\" at /book (1:34)"
   );
 }
@@ -0,0 +1,171 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::fmt::Debug;
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::Error;
+use yaserde::{YaDeserialize, YaSerialize};
+
+fn convert_and_validate<T>(model: T, content: &str)
+where
+  T: YaSerialize + YaDeserialize + PartialEq + Debug,
+{
+  let data: Result<String, Error> = to_string(&model);
+  assert_eq!(data.unwrap(), String::from(content));
+
+  let loaded: Result<T, Error> = from_str(content);
+  assert_eq!(loaded.unwrap(), model);
+}
+
+#[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+#[yaserde(root = "shape", variant_element)]
+pub enum Shape {
+  #[yaserde(rename = "circle")]
+  Circle {
+    #[yaserde(attribute)]
+    r: u32,
+  },
+  #[yaserde(rename = "rect")]
+  Rect {
+    #[yaserde(attribute)]
+    w: u32,
+    #[yaserde(attribute)]
+    h: u32,
+  },
+}
+
+#[test]
+fn flatten_struct() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "metadata")]
+  pub struct Metadata {
+    #[yaserde(attribute)]
+    lang: String,
+    author: String,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    #[yaserde(attribute)]
+    id: u32,
+    title: String,
+    #[yaserde(flatten)]
+    metadata: Metadata,
+  }
+
+  let model = Book {
+    id: 1,
+    title: "Little prince".to_string(),
+    metadata: Metadata {
+      lang: "fr".to_string(),
+      author: "Antoine de Saint-Exupéry".to_string(),
+    },
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book id=\"1\" lang=\"fr\"><title>Little prince</title><author>Antoine de Saint-Exupéry</author></book>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn flatten_option_attributes() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "audit")]
+  pub struct Audit {
+    #[yaserde(attribute)]
+    created: u32,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    title: String,
+    #[yaserde(flatten)]
+    audit: Option<Audit>,
+  }
+
+  convert_and_validate(
+    Book {
+      title: "Little prince".to_string(),
+      audit: Some(Audit { created: 2020 }),
+    },
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book created=\"2020\"><title>Little prince</title></book>",
+  );
+
+  convert_and_validate(
+    Book {
+      title: "Little prince".to_string(),
+      audit: None,
+    },
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book><title>Little prince</title></book>",
+  );
+}
+
+#[test]
+fn flatten_enum() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "frame")]
+  pub struct Frame {
+    #[yaserde(attribute)]
+    name: String,
+    #[yaserde(flatten)]
+    shape: Option<Shape>,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "drawing")]
+  pub struct Drawing {
+    #[yaserde(attribute)]
+    name: String,
+    #[yaserde(flatten)]
+    shapes: Vec<Shape>,
+  }
+
+  convert_and_validate(
+    Frame {
+      name: "empty".to_string(),
+      shape: None,
+    },
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><frame name=\"empty\" />",
+  );
+
+  convert_and_validate(
+    Frame {
+      name: "round".to_string(),
+      shape: Some(Shape::Circle { r: 1 }),
+    },
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><frame name=\"round\"><circle r=\"1\" /></frame>",
+  );
+
+  let model = Drawing {
+    name: "sketch".to_string(),
+    shapes: vec![
+      Shape::Circle { r: 1 },
+      Shape::Rect { w: 2, h: 3 },
+      Shape::Circle { r: 4 },
+    ],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><drawing name=\"sketch\"><circle r=\"1\" /><rect w=\"2\" h=\"3\" /><circle r=\"4\" /></drawing>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn flatten_error_path() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "drawing")]
+  pub struct Drawing {
+    #[yaserde(flatten)]
+    shapes: Vec<Shape>,
+  }
+
+  let content = "<drawing><circle r=\"1\" /><circle r=\"one\" /></drawing>";
+  let error = from_str::<Drawing>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/drawing/circle[2]/@r"));
+}
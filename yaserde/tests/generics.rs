@@ -0,0 +1,249 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::Error;
+use yaserde::{YaDeserialize, YaSerialize};
+
+fn convert_and_validate<T>(model: T, content: &str)
+where
+  T: YaSerialize + YaDeserialize + PartialEq + Debug,
+{
+  let data: Result<String, Error> = to_string(&model);
+  assert_eq!(data.unwrap(), String::from(content));
+
+  let loaded: Result<T, Error> = from_str(content);
+  assert_eq!(loaded.unwrap(), model);
+}
+
+#[test]
+fn generic_struct() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    title: String,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "page")]
+  pub struct Page<T> {
+    #[yaserde(attribute)]
+    number: u32,
+    first: T,
+    #[yaserde(rename = "book")]
+    items: Vec<T>,
+    last: Option<T>,
+  }
+
+  let model = Page {
+    number: 2,
+    first: Book {
+      title: String::from("Little prince"),
+    },
+    items: vec![
+      Book {
+        title: String::from("Vol de nuit"),
+      },
+      Book {
+        title: String::from("Terre des hommes"),
+      },
+    ],
+    last: None,
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><page number=\"2\"><first><title>Little prince</title></first><book><title>Vol de nuit</title></book><book><title>Terre des hommes</title></book></page>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn generic_struct_with_where_clause() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "item")]
+  pub struct Item {
+    #[yaserde(attribute)]
+    id: u32,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "list")]
+  pub struct List<T>
+  where
+    T: Debug,
+  {
+    #[yaserde(rename = "item")]
+    items: Vec<T>,
+  }
+
+  let model = List {
+    items: vec![Item { id: 1 }, Item { id: 2 }],
+  };
+
+  let content =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><list><item id=\"1\" /><item id=\"2\" /></list>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn generic_struct_with_bound() {
+  pub trait Catalog {
+    type Entry;
+  }
+
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    title: String,
+  }
+
+  // not serializable itself, only its entries are
+  #[derive(PartialEq, Debug)]
+  pub struct Books;
+
+  impl Catalog for Books {
+    type Entry = Book;
+  }
+
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "shelf", bound = "C::Entry: YaSerialize")]
+  pub struct Shelf<C: Catalog> {
+    #[yaserde(rename = "book")]
+    books: Vec<C::Entry>,
+  }
+
+  let model: Shelf<Books> = Shelf {
+    books: vec![Book {
+      title: String::from("Little prince"),
+    }],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><shelf><book><title>Little prince</title></book></shelf>";
+  let data: Result<String, Error> = to_string(&model);
+  assert_eq!(data.unwrap(), String::from(content));
+}
+
+#[test]
+fn generic_struct_with_split_bound() {
+  pub trait Catalog {
+    type Entry;
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    title: String,
+  }
+
+  #[derive(PartialEq, Debug)]
+  pub struct Books;
+
+  impl Catalog for Books {
+    type Entry = Book;
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(
+    root = "shelf",
+    bound(
+      serialize = "C::Entry: YaSerialize",
+      deserialize = "C::Entry: YaDeserialize"
+    )
+  )]
+  pub struct Shelf<C: Catalog> {
+    #[yaserde(rename = "book")]
+    books: Vec<C::Entry>,
+  }
+
+  let model: Shelf<Books> = Shelf {
+    books: vec![Book {
+      title: String::from("Little prince"),
+    }],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><shelf><book><title>Little prince</title></book></shelf>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn generic_enum() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    title: String,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "entry")]
+  pub enum Entry<T> {
+    Empty,
+    Item(T),
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "shelf")]
+  pub struct Shelf<T> {
+    #[yaserde(rename = "entry")]
+    entries: Vec<Entry<T>>,
+  }
+
+  let model = Shelf {
+    entries: vec![
+      Entry::Item(Book {
+        title: String::from("Little prince"),
+      }),
+      Entry::Empty,
+    ],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><shelf><entry><Item><title>Little prince</title></Item></entry><entry>Empty</entry></shelf>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn generic_struct_with_skipped_param() {
+  // only bounded through the fields actually read and written
+  #[derive(PartialEq, Debug)]
+  pub struct Unit;
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "tagged")]
+  pub struct Tagged<T> {
+    #[yaserde(attribute)]
+    id: u32,
+    #[yaserde(skip)]
+    marker: PhantomData<T>,
+  }
+
+  let model: Tagged<Unit> = Tagged {
+    id: 1,
+    marker: PhantomData,
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><tagged id=\"1\" />";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn generic_struct_with_lifetime() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "page")]
+  pub struct Page<'a> {
+    title: Cow<'a, str>,
+  }
+
+  let model = Page {
+    title: Cow::Borrowed("Little prince"),
+  };
+
+  let content =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><page><title>Little prince</title></page>";
+  convert_and_validate(model, content);
+}
@@ -0,0 +1,98 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn list_attribute() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "point")]
+  pub struct Point {
+    #[yaserde(attribute, list)]
+    coords: Vec<f64>,
+    #[yaserde(attribute, list, separator = ",")]
+    tags: Option<Vec<String>>,
+  }
+
+  let model = Point {
+    coords: vec![1.5, 2.5, 3.75],
+    tags: Some(vec!["a".to_string(), "b c".to_string()]),
+  };
+  let content =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><point coords=\"1.5 2.5 3.75\" tags=\"a,b c\" />";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Point = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let loaded: Point = from_str("<point coords=\"  1.5\n\t2 \" tags=\"a , b\" />").unwrap();
+  assert_eq!(
+    loaded,
+    Point {
+      coords: vec![1.5, 2.0],
+      tags: Some(vec!["a".to_string(), "b".to_string()]),
+    }
+  );
+
+  let loaded: Point = from_str("<point coords=\"\" />").unwrap();
+  assert_eq!(
+    loaded,
+    Point {
+      coords: vec![],
+      tags: None,
+    }
+  );
+}
+
+#[test]
+fn list_content() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "values")]
+  pub struct Values {
+    #[yaserde(attribute)]
+    unit: String,
+    #[yaserde(text, list)]
+    values: Vec<u32>,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "shape")]
+  pub struct Shape {
+    #[yaserde(list, separator = ";")]
+    sizes: Vec<u32>,
+    values: Values,
+  }
+
+  let model = Shape {
+    sizes: vec![3, 4],
+    values: Values {
+      unit: "px".to_string(),
+      values: vec![10, 20, 30],
+    },
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><shape><sizes>3;4</sizes><values unit=\"px\">10 20 30</values></shape>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Shape = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn list_error() {
+  #[derive(YaDeserialize, PartialEq, Debug)]
+  #[yaserde(root = "point")]
+  pub struct Point {
+    #[yaserde(attribute, list)]
+    coords: Vec<u32>,
+  }
+
+  let error = from_str::<Point>("<point coords=\"1 x\" />").unwrap_err();
+  assert_eq!(error.path(), Some("/point/@coords[2]"));
+}
@@ -0,0 +1,115 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn map_as_elements() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "config")]
+  pub struct Config {
+    settings: BTreeMap<String, u32>,
+    labels: Option<HashMap<String, String>>,
+  }
+
+  let mut settings = BTreeMap::new();
+  settings.insert("height".to_string(), 20);
+  settings.insert("width".to_string(), 10);
+  let mut labels = HashMap::new();
+  labels.insert("title".to_string(), "Settings".to_string());
+
+  let model = Config {
+    settings,
+    labels: Some(labels),
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><config><settings><height>20</height><width>10</width></settings><labels><title>Settings</title></labels></config>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Config = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let loaded: Config = from_str("<config />").unwrap();
+  assert_eq!(
+    loaded,
+    Config {
+      settings: BTreeMap::new(),
+      labels: None,
+    }
+  );
+}
+
+#[test]
+fn map_as_entries() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "option")]
+  pub struct Value {
+    #[yaserde(attribute)]
+    unit: String,
+    #[yaserde(text)]
+    value: u32,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "config")]
+  pub struct Config {
+    #[yaserde(rename = "entry", key_attribute = "key")]
+    settings: BTreeMap<u32, String>,
+    #[yaserde(rename = "option", key_attribute = "name")]
+    options: BTreeMap<String, Value>,
+  }
+
+  let mut settings = BTreeMap::new();
+  settings.insert(1, "one".to_string());
+  settings.insert(2, "two".to_string());
+  let mut options = BTreeMap::new();
+  options.insert(
+    "width".to_string(),
+    Value {
+      unit: "px".to_string(),
+      value: 10,
+    },
+  );
+
+  let model = Config { settings, options };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><config><entry key=\"1\">one</entry><entry key=\"2\">two</entry><option name=\"width\" unit=\"px\">10</option></config>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Config = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let error = from_str::<Config>("<config><entry>one</entry></config>").unwrap_err();
+  assert_eq!(
+    error.to_string(),
+    "missing attribute \"key\" at /config/entry (1:9)"
+  );
+}
+
+#[test]
+fn map_invalid_key() {
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "config")]
+  pub struct Config {
+    settings: BTreeMap<String, u32>,
+  }
+
+  for key in &["line height", "1x", "ns:width", ""] {
+    let mut settings = BTreeMap::new();
+    settings.insert(key.to_string(), 20);
+    let error = to_string(&Config { settings }).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      format!(
+        "map key {:?} is not a valid element name, write the entries with `key_attribute`",
+        key
+      )
+    );
+  }
+}
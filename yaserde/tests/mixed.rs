@@ -0,0 +1,137 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+#[yaserde(root = "content", variant_element)]
+pub enum Inline {
+  #[yaserde(text)]
+  Text(String),
+  #[yaserde(rename = "b")]
+  Bold(String),
+  #[yaserde(rename = "a")]
+  Link(Link),
+  #[yaserde(rename = "br")]
+  Break {},
+}
+
+#[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+#[yaserde(root = "a")]
+pub struct Link {
+  #[yaserde(attribute)]
+  href: String,
+  #[yaserde(mixed)]
+  content: Vec<Inline>,
+}
+
+#[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+#[yaserde(root = "para")]
+pub struct Para {
+  #[yaserde(attribute)]
+  id: String,
+  #[yaserde(mixed)]
+  content: Vec<Inline>,
+}
+
+#[test]
+fn mixed_content() {
+  let model = Para {
+    id: "p1".to_string(),
+    content: vec![
+      Inline::Text("Hello ".to_string()),
+      Inline::Bold("world".to_string()),
+      Inline::Text("! See ".to_string()),
+      Inline::Link(Link {
+        href: "/docs".to_string(),
+        content: vec![
+          Inline::Text("the ".to_string()),
+          Inline::Bold("docs".to_string()),
+        ],
+      }),
+      Inline::Break {},
+      Inline::Text(" \n ".to_string()),
+    ],
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><para id=\"p1\">Hello <b>world</b>! See <a href=\"/docs\">the <b>docs</b></a><br /> \n </para>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Para = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn mixed_content_in_document() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "doc")]
+  pub struct Doc {
+    title: String,
+    #[yaserde(rename = "para")]
+    paras: Vec<Para>,
+  }
+
+  // whitespace is only kept inside the mixed content
+  let content = "<doc>\n  <title> Guide </title>\n  <para id=\"p1\"> A <b> B </b></para>\n  <para id=\"p2\"/>\n</doc>";
+  let loaded: Doc = from_str(content).unwrap();
+  assert_eq!(
+    loaded,
+    Doc {
+      title: "Guide".to_string(),
+      paras: vec![
+        Para {
+          id: "p1".to_string(),
+          content: vec![
+            Inline::Text(" A ".to_string()),
+            Inline::Bold(" B ".to_string()),
+          ],
+        },
+        Para {
+          id: "p2".to_string(),
+          content: vec![],
+        },
+      ],
+    }
+  );
+}
+
+#[test]
+fn mixed_content_flattened() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "body")]
+  pub struct Body {
+    #[yaserde(mixed)]
+    content: Vec<Inline>,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "note")]
+  pub struct Note {
+    #[yaserde(attribute)]
+    id: u32,
+    #[yaserde(flatten)]
+    body: Body,
+  }
+
+  let model = Note {
+    id: 1,
+    body: Body {
+      content: vec![
+        Inline::Text(" a ".to_string()),
+        Inline::Bold("b".to_string()),
+        Inline::Text(" ".to_string()),
+      ],
+    },
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><note id=\"1\"> a <b>b</b> </note>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Note = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
@@ -0,0 +1,87 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn nested_sequences() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "matrix")]
+  pub struct Matrix {
+    #[yaserde(rename = "cell", inner_wrapper = "row")]
+    rows: Vec<VecDeque<u32>>,
+  }
+
+  let model = Matrix {
+    rows: vec![
+      vec![1, 2].into_iter().collect(),
+      VecDeque::new(),
+      vec![3].into_iter().collect(),
+    ],
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><matrix><row><cell>1</cell><cell>2</cell></row><row /><row><cell>3</cell></row></matrix>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Matrix = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let loaded: Matrix =
+    from_str("<matrix><row><cell>1</cell><other>4</other>\n</row><cell>5</cell></matrix>").unwrap();
+  assert_eq!(
+    loaded,
+    Matrix {
+      rows: vec![vec![1].into_iter().collect()],
+    }
+  );
+}
+
+#[test]
+fn nested_sequences_default_wrapper() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "grid")]
+  pub struct Grid {
+    cells: Option<Vec<Vec<Vec<String>>>>,
+  }
+
+  let model = Grid {
+    cells: Some(vec![vec![vec!["a".to_string()], vec![]]]),
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><grid><cells><cells><cells><cells>a</cells></cells><cells /></cells></cells></grid>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Grid = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let loaded: Grid = from_str("<grid />").unwrap();
+  assert_eq!(loaded, Grid { cells: None });
+}
+
+#[test]
+fn optional_items() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "record")]
+  pub struct Record {
+    #[yaserde(rename = "value")]
+    values: Vec<Option<String>>,
+    #[yaserde(rename = "point", inner_wrapper = "series")]
+    series: Vec<Option<Vec<u32>>>,
+  }
+
+  let model = Record {
+    values: vec![Some("a".to_string()), None, Some("".to_string())],
+    series: vec![Some(vec![1]), None, Some(vec![])],
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><record><value>a</value><value xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:nil=\"true\" /><value></value><series><point>1</point></series><series xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:nil=\"true\" /><series /></record>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Record = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
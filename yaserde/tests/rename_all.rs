@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn rename_all_struct() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book", rename_all = "camelCase")]
+  pub struct Book {
+    #[yaserde(attribute)]
+    page_count: u32,
+    first_author: String,
+    #[yaserde(rename = "Title")]
+    book_title: String,
+  }
+
+  let model = Book {
+    page_count: 320,
+    first_author: "Antoine de Saint-Exupéry".to_string(),
+    book_title: "Little prince".to_string(),
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book pageCount=\"320\"><firstAuthor>Antoine de Saint-Exupéry</firstAuthor><Title>Little prince</Title></book>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn rename_all_enum() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "color", rename_all = "kebab-case")]
+  pub enum Color {
+    DarkRed,
+    #[yaserde(rename = "sky")]
+    LightBlue,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "palette", rename_all = "SCREAMING_SNAKE_CASE")]
+  pub struct Palette {
+    main_color: Color,
+    other_colors: Vec<Color>,
+  }
+
+  let model = Palette {
+    main_color: Color::DarkRed,
+    other_colors: vec![Color::LightBlue],
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><palette><MAIN_COLOR>dark-red</MAIN_COLOR><OTHER_COLORS>sky</OTHER_COLORS></palette>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Palette = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
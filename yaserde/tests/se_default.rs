@@ -7,12 +7,13 @@ extern crate yaserde_derive;
 
 use std::io::Write;
 use yaserde::ser::to_string;
+use yaserde::Error;
 use yaserde::YaSerialize;
 
 macro_rules! convert_and_validate {
   ($model:expr, $content:expr) => {
-    let data: Result<String, String> = to_string(&$model);
-    assert_eq!(data, Ok(String::from($content)));
+    let data: Result<String, Error> = to_string(&$model);
+    assert_eq!(data.unwrap(), String::from($content));
   };
 }
 
@@ -29,14 +30,14 @@ fn se_default_field_string() {
     background: String,
   }
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base />";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base />";
   convert_and_validate!(
     XmlStruct {
       background: "my_default_value".to_string(),
     },
     content
   );
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><background>my_value</background></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><background>my_value</background></base>";
   convert_and_validate!(
     XmlStruct {
       background: "my_value".to_string(),
@@ -58,10 +59,10 @@ fn se_default_field_boolean() {
     background: bool,
   }
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base />";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base />";
   convert_and_validate!(XmlStruct { background: true }, content);
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><background>false</background></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><background>false</background></base>";
   convert_and_validate!(
     XmlStruct {
       background: false,
@@ -83,7 +84,7 @@ fn se_default_field_number() {
     background: u8,
   }
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base />";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base />";
   convert_and_validate!(XmlStruct { background: 6 }, content);
 }
 
@@ -100,7 +101,7 @@ fn se_default_attribute_string() {
     background: String,
   }
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base />";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base />";
   convert_and_validate!(
     XmlStruct {
       background: "my_default_value".to_string(),
@@ -108,3 +109,41 @@ fn se_default_attribute_string() {
     content
   );
 }
+
+#[test]
+fn se_skip_serializing_if() {
+  fn is_zero(value: &u32) -> bool {
+    *value == 0
+  }
+
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "base")]
+  pub struct XmlStruct {
+    #[yaserde(attribute, skip_serializing_if = "is_zero")]
+    count: u32,
+    #[yaserde(skip_serializing_if = "String::is_empty")]
+    title: String,
+    #[yaserde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+  }
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base />";
+  convert_and_validate!(
+    XmlStruct {
+      count: 0,
+      title: String::new(),
+      tags: vec![],
+    },
+    content
+  );
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base count=\"2\"><title>Dune</title><tags>novel</tags></base>";
+  convert_and_validate!(
+    XmlStruct {
+      count: 2,
+      title: "Dune".to_string(),
+      tags: vec!["novel".to_string()],
+    },
+    content
+  );
+}
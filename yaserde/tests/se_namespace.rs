@@ -7,12 +7,13 @@ extern crate yaserde_derive;
 
 use std::io::Write;
 use yaserde::ser::to_string;
+use yaserde::Error;
 use yaserde::YaSerialize;
 
 macro_rules! convert_and_validate {
   ($model:expr, $content:expr) => {
-    let data: Result<String, String> = to_string(&$model);
-    assert_eq!(data, Ok(String::from($content)));
+    let data: Result<String, Error> = to_string(&$model);
+    assert_eq!(data.unwrap(), String::from($content));
   };
 }
 
@@ -33,7 +34,7 @@ fn ser_struct_namespace() {
     item: "something".to_string(),
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><ns:root xmlns:ns=\"http://www.sample.com/ns/domain\"><ns:item>something</ns:item></ns:root>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ns:root xmlns:ns=\"http://www.sample.com/ns/domain\"><ns:item>something</ns:item></ns:root>";
   convert_and_validate!(model, content);
 }
 
@@ -52,7 +53,7 @@ fn ser_enum_namespace() {
 
   let model = XmlStruct::Item;
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><ns:root xmlns:ns=\"http://www.sample.com/ns/domain\">ns:Item</ns:root>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ns:root xmlns:ns=\"http://www.sample.com/ns/domain\">ns:Item</ns:root>";
   convert_and_validate!(model, content);
 }
 
@@ -76,7 +77,7 @@ fn ser_struct_multi_namespace() {
     item_2: "something 2".to_string(),
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\"><ns1:item_1>something 1</ns1:item_1><ns2:item_2>something 2</ns2:item_2></root>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\"><ns1:item_1>something 1</ns1:item_1><ns2:item_2>something 2</ns2:item_2></root>";
   convert_and_validate!(model, content);
 }
 
@@ -96,10 +97,10 @@ fn ser_enum_multi_namespace() {
   }
 
   let model1 = XmlStruct::Item1;
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\">ns1:Item1</root>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\">ns1:Item1</root>";
   convert_and_validate!(model1, content);
   let model2 = XmlStruct::Item2;
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\">ns2:Item2</root>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\">ns2:Item2</root>";
   convert_and_validate!(model2, content);
 }
 
@@ -123,7 +124,7 @@ fn ser_struct_attribute_namespace() {
     item_2: "something 2".to_string(),
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\" ns2:item_2=\"something 2\"><ns1:item_1>something 1</ns1:item_1></root>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><root xmlns:ns1=\"http://www.sample.com/ns/domain1\" xmlns:ns2=\"http://www.sample.com/ns/domain2\" ns2:item_2=\"something 2\"><ns1:item_1>something 1</ns1:item_1></root>";
   convert_and_validate!(model, content);
 }
 
@@ -143,6 +144,6 @@ fn ser_struct_default_namespace() {
     item: "something".to_string(),
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:ttm=\"http://www.w3.org/ns/ttml#metadata\"><item>something</item></tt>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:ttm=\"http://www.w3.org/ns/ttml#metadata\"><item>something</item></tt>";
   convert_and_validate!(model, content);
 }
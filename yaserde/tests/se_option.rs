@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls, clippy::unnecessary_cast)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,6 +9,7 @@ extern crate yaserde_derive;
 
 use std::io::Write;
 use yaserde::ser::to_string;
+use yaserde::Error;
 use yaserde::YaSerialize;
 
 macro_rules! convert_and_validate {
@@ -18,7 +21,7 @@ macro_rules! convert_and_validate {
     }
     let model = Data { item: $value };
 
-    let data: Result<String, String> = to_string(&model);
+    let data: Result<String, Error> = to_string(&model);
 
     let content = if let Some(str_value) = $content {
       "<data><item>".to_string() + str_value + "</item></data>"
@@ -26,8 +29,8 @@ macro_rules! convert_and_validate {
       "<data />".to_string()
     };
 
-    let content = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>") + &content;
-    assert_eq!(data, Ok(content));
+    let content = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>") + &content;
+    assert_eq!(data.unwrap(), content);
   }};
 }
 
@@ -41,7 +44,7 @@ macro_rules! convert_and_validate_as_attribute {
     }
     let model = Data { item: $value };
 
-    let data: Result<String, String> = to_string(&model);
+    let data: Result<String, Error> = to_string(&model);
 
     let content = if let Some(str_value) = $content {
       "<data item=\"".to_string() + str_value + "\" />"
@@ -49,8 +52,8 @@ macro_rules! convert_and_validate_as_attribute {
       "<data />".to_string()
     };
 
-    let content = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>") + &content;
-    assert_eq!(data, Ok(content));
+    let content = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>") + &content;
+    assert_eq!(data.unwrap(), content);
   }};
 }
 
@@ -86,9 +89,13 @@ fn ser_option() {
   convert_and_validate!(f64, None, None);
 
   convert_and_validate!(Vec<u8>, None, None);
-  convert_and_validate!(Vec<u8>, Some(vec![0]), Some("0"));
+  convert_and_validate!(Vec<u8>, Some(vec![0]), Some("<item>0</item>"));
   convert_and_validate!(Vec<String>, None, None);
-  convert_and_validate!(Vec<String>, Some(vec!["test".to_string()]), Some("test"));
+  convert_and_validate!(
+    Vec<String>,
+    Some(vec!["test".to_string()]),
+    Some("<item>test</item>")
+  );
 
   convert_and_validate_as_attribute!(String, Some("test".to_string()), Some("test"));
   convert_and_validate_as_attribute!(String, None, None);
@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_cast)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,6 +9,7 @@ extern crate yaserde_derive;
 
 use std::io::Write;
 use yaserde::ser::to_string;
+use yaserde::Error;
 use yaserde::YaSerialize;
 
 macro_rules! convert_and_validate {
@@ -18,15 +21,15 @@ macro_rules! convert_and_validate {
     }
     let model = Data { item: $value };
 
-    let data: Result<String, String> = to_string(&model);
+    let data: Result<String, Error> = to_string(&model);
     let content = if $content == "" {
-      String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><data />")
+      String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><data />")
     } else {
-      String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><data><item>")
+      String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><data><item>")
         + $content
         + "</item></data>"
     };
-    assert_eq!(data, Ok(content));
+    assert_eq!(data.unwrap(), content);
   }};
 }
 
@@ -40,13 +43,30 @@ macro_rules! convert_and_validate_as_attribute {
     }
     let model = Data { item: $value };
 
-    let data: Result<String, String> = to_string(&model);
+    let data: Result<String, Error> = to_string(&model);
     let content =
-      String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><data item=\"") + $content + "\" />";
-    assert_eq!(data, Ok(content));
+      String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><data item=\"") + $content + "\" />";
+    assert_eq!(data.unwrap(), content);
   }};
 }
 
+type Text = String;
+
+mod units {
+  use std::io::Write;
+  use yaserde::ser::Serializer;
+  use yaserde::{Error, YaSerialize};
+
+  #[derive(PartialEq, Debug)]
+  pub struct Celsius(pub f32);
+
+  impl YaSerialize for Celsius {
+    fn serialize<W: Write>(&self, writer: &mut Serializer<W>) -> Result<(), Error> {
+      writer.write_text_value(&format!("{}°C", self.0))
+    }
+  }
+}
+
 #[test]
 fn ser_type() {
   convert_and_validate!(String, "test".to_string(), "test");
@@ -67,6 +87,10 @@ fn ser_type() {
   convert_and_validate!(f64, -12.5 as f64, "-12.5");
   convert_and_validate!(Vec<String>, vec![], "");
   convert_and_validate!(Vec<String>, vec!["test".to_string()], "test");
+  convert_and_validate!(std::string::String, "test".to_string(), "test");
+  convert_and_validate!(Text, "test".to_string(), "test");
+  convert_and_validate!(units::Celsius, units::Celsius(-12.5), "-12.5°C");
+  convert_and_validate!(Option<::std::string::String>, Some("test".to_string()), "test");
 
   convert_and_validate_as_attribute!(String, "test".to_string(), "test");
   convert_and_validate_as_attribute!(bool, true, "true");
@@ -84,4 +108,7 @@ fn ser_type() {
   convert_and_validate_as_attribute!(i64, -12 as i64, "-12");
   convert_and_validate_as_attribute!(f32, -12.5 as f32, "-12.5");
   convert_and_validate_as_attribute!(f64, -12.5 as f64, "-12.5");
+  convert_and_validate_as_attribute!(std::string::String, "test".to_string(), "test");
+  convert_and_validate_as_attribute!(Text, "test".to_string(), "test");
+  convert_and_validate_as_attribute!(units::Celsius, units::Celsius(-12.5), "-12.5°C");
 }
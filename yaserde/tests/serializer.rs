@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls)]
+
 #[macro_use]
 extern crate log;
 extern crate xml;
@@ -7,12 +9,13 @@ extern crate yaserde_derive;
 
 use std::io::Write;
 use yaserde::ser::to_string;
+use yaserde::Error;
 use yaserde::YaSerialize;
 
 macro_rules! convert_and_validate {
   ($model: expr, $content: expr) => {
-    let data: Result<String, String> = to_string(&$model);
-    assert_eq!(data, Ok(String::from($content)));
+    let data: Result<String, Error> = to_string(&$model);
+    assert_eq!(data.unwrap(), String::from($content));
   };
 }
 
@@ -28,7 +31,7 @@ fn ser_basic() {
     item: "something".to_string(),
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><item>something</item></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><item>something</item></base>";
   convert_and_validate!(model, content);
 }
 
@@ -44,7 +47,7 @@ fn ser_list_of_items() {
     items: vec!["something1".to_string(), "something2".to_string()],
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><items>something1</items><items>something2</items></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><items>something1</items><items>something2</items></base>";
   convert_and_validate!(model, content);
 
   #[derive(YaSerialize, PartialEq, Debug)]
@@ -70,7 +73,7 @@ fn ser_list_of_items() {
     ],
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><items><field>something1</field></items><items><field>something2</field></items></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><items><field>something1</field></items><items><field>something2</field></items></base>";
   convert_and_validate!(model2, content);
 }
 
@@ -113,7 +116,7 @@ fn se_attributes() {
     },
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base item=\"something\"><sub subitem=\"sub-something\" /></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base item=\"something\"><sub subitem=\"sub-something\" /></base>";
   convert_and_validate!(model, content);
 }
 
@@ -157,7 +160,7 @@ fn ser_rename() {
     },
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base Item=\"something\"><sub sub_item=\"sub_something\" /></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base Item=\"something\"><sub sub_item=\"sub_something\" /></base>";
   convert_and_validate!(model, content);
 }
 
@@ -206,7 +209,7 @@ fn ser_text_content_with_attributes() {
     },
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base Item=\"something\"><sub sub_item=\"sub_something\">text_content</sub></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base Item=\"something\"><sub sub_item=\"sub_something\">text_content</sub></base>";
   convert_and_validate!(model, content);
 }
 
@@ -257,7 +260,7 @@ fn ser_enum() {
     color: Color::Black,
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><color>Black</color></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><color>Black</color></base>";
   convert_and_validate!(model, content);
 
   let model = XmlStruct {
@@ -273,7 +276,79 @@ fn ser_enum() {
     },
   };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base><color><enabled>true</enabled><color><red>0</red><green>128</green><blue>255</blue></color><alpha>Opaque</alpha><alphas>Opaque</alphas><alphas>Transparent</alphas></color></base>";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base><color><enabled>true</enabled><color><red>0</red><green>128</green><blue>255</blue></color><alpha>Opaque</alpha><alphas>Opaque</alphas><alphas>Transparent</alphas></color></base>";
+  convert_and_validate!(model, content);
+}
+
+#[test]
+fn ser_complex_enum() {
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "drawing")]
+  pub struct Drawing {
+    shapes: Vec<Shape>,
+  }
+
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "shape")]
+  pub enum Shape {
+    #[yaserde(rename = "circle")]
+    Circle {
+      #[yaserde(attribute)]
+      r: u32,
+    },
+    #[yaserde(rename = "label")]
+    Label(String),
+    #[yaserde(rename = "line")]
+    Line(#[yaserde(rename = "from")] u32, #[yaserde(rename = "to")] u32),
+  }
+
+  let model = Drawing {
+    shapes: vec![
+      Shape::Circle { r: 1 },
+      Shape::Label("A".to_string()),
+      Shape::Line(4, 5),
+    ],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><drawing><shapes r=\"1\" /><shapes><label>A</label></shapes><shapes><line><from>4</from><to>5</to></line></shapes></drawing>";
+  convert_and_validate!(model, content);
+}
+
+#[test]
+fn ser_variant_element() {
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "drawing")]
+  pub struct Drawing {
+    shapes: Vec<Shape>,
+  }
+
+  #[derive(YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "shape", variant_element)]
+  pub enum Shape {
+    #[yaserde(rename = "circle")]
+    Circle {
+      #[yaserde(attribute)]
+      r: u32,
+    },
+    #[yaserde(rename = "rect")]
+    Rect {
+      #[yaserde(attribute)]
+      w: u32,
+      label: String,
+    },
+  }
+
+  let model = Drawing {
+    shapes: vec![
+      Shape::Circle { r: 1 },
+      Shape::Rect {
+        w: 2,
+        label: "A".to_string(),
+      },
+    ],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><drawing><shapes><circle r=\"1\" /></shapes><shapes><rect w=\"2\"><label>A</label></rect></shapes></drawing>";
   convert_and_validate!(model, content);
 }
 
@@ -295,6 +370,6 @@ fn ser_attribute_enum() {
 
   let model = XmlStruct { color: Color::Pink };
 
-  let content = "<?xml version=\"1.0\" encoding=\"utf-8\"?><base color=\"pink\" />";
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><base color=\"pink\" />";
   convert_and_validate!(model, content);
 }
@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[derive(PartialEq, Debug, Default)]
+pub struct Handle(u32);
+
+#[test]
+fn skip() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "file")]
+  pub struct File {
+    #[yaserde(attribute)]
+    path: String,
+    #[yaserde(skip)]
+    handle: Handle,
+    #[yaserde(skip)]
+    lines: Vec<Handle>,
+  }
+
+  let model = File {
+    path: "/tmp/a".to_string(),
+    handle: Handle(3),
+    lines: vec![Handle(1)],
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><file path=\"/tmp/a\" />";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: File = from_str(content).unwrap();
+  assert_eq!(
+    loaded,
+    File {
+      path: "/tmp/a".to_string(),
+      handle: Handle(0),
+      lines: vec![],
+    }
+  );
+}
+
+#[test]
+fn skip_one_direction() {
+  fn default_index() -> u32 {
+    42
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "entry")]
+  pub struct Entry {
+    #[yaserde(attribute, skip_serializing)]
+    legacy: String,
+    #[yaserde(skip_deserializing, default = "default_index")]
+    index: u32,
+  }
+
+  let model = Entry {
+    legacy: "old".to_string(),
+    index: 7,
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><entry><index>7</index></entry>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Entry = from_str("<entry legacy=\"old\"><index>7</index></entry>").unwrap();
+  assert_eq!(
+    loaded,
+    Entry {
+      legacy: "old".to_string(),
+      index: 42,
+    }
+  );
+}
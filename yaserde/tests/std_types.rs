@@ -0,0 +1,138 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::io::{Read, Write};
+use std::rc::Rc;
+use std::sync::Arc;
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn recursive_struct() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "node")]
+  pub struct Node {
+    #[yaserde(attribute)]
+    name: String,
+    left: Option<Box<Node>>,
+    right: Option<Box<Node>>,
+  }
+
+  let model = Node {
+    name: "root".to_string(),
+    left: Some(Box::new(Node {
+      name: "a".to_string(),
+      left: None,
+      right: Some(Box::new(Node {
+        name: "b".to_string(),
+        left: None,
+        right: None,
+      })),
+    })),
+    right: None,
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><node name=\"root\"><left name=\"a\"><right name=\"b\" /></left></node>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Node = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn pointers() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    #[yaserde(attribute)]
+    lang: Cow<'static, str>,
+    author: Rc<String>,
+    title: Arc<String>,
+    pages: Box<u32>,
+  }
+
+  let model = Book {
+    lang: Cow::Borrowed("fr"),
+    author: Rc::new("Antoine de Saint-Exupéry".to_string()),
+    title: Arc::new("Little prince".to_string()),
+    pages: Box::new(96),
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book lang=\"fr\"><author>Antoine de Saint-Exupéry</author><title>Little prince</title><pages>96</pages></book>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn sequences() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "library")]
+  pub struct Library {
+    #[yaserde(rename = "queued")]
+    queue: VecDeque<String>,
+    #[yaserde(rename = "tag")]
+    tags: BTreeSet<String>,
+    #[yaserde(wrapper = "years", item = "year")]
+    years: Option<HashSet<u32>>,
+  }
+
+  let model = Library {
+    queue: vec!["b".to_string(), "a".to_string()].into_iter().collect(),
+    tags: vec!["tale".to_string(), "classic".to_string()]
+      .into_iter()
+      .collect(),
+    years: Some(vec![1943].into_iter().collect()),
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><library><queued>b</queued><queued>a</queued><tag>classic</tag><tag>tale</tag><years><year>1943</year></years></library>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Library = from_str(
+    "<library><queued>b</queued><tag>tale</tag><queued>a</queued><tag>classic</tag><tag>tale</tag><years><year>1943</year></years></library>",
+  )
+  .unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn sequences_as_values() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "shelf")]
+  pub struct Shelf<T> {
+    #[yaserde(attribute)]
+    id: u32,
+    #[yaserde(rename = "tag")]
+    tags: T,
+    label: String,
+  }
+
+  let model = Shelf {
+    id: 1,
+    tags: vec!["tale".to_string(), "classic".to_string()]
+      .into_iter()
+      .collect::<BTreeSet<String>>(),
+    label: "A".to_string(),
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><shelf id=\"1\"><tag>classic</tag><tag>tale</tag><label>A</label></shelf>";
+  assert_eq!(to_string(&model).unwrap(), content);
+  let loaded: Shelf<BTreeSet<String>> = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let loaded: Shelf<VecDeque<String>> = from_str(content).unwrap();
+  assert_eq!(
+    loaded.tags,
+    vec!["classic".to_string(), "tale".to_string()]
+      .into_iter()
+      .collect::<VecDeque<String>>()
+  );
+}
@@ -0,0 +1,115 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::fmt::Debug;
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::Error;
+use yaserde::{YaDeserialize, YaSerialize};
+
+fn convert_and_validate<T>(model: T, content: &str)
+where
+  T: YaSerialize + YaDeserialize + PartialEq + Debug,
+{
+  let data: Result<String, Error> = to_string(&model);
+  assert_eq!(data.unwrap(), String::from(content));
+
+  let loaded: Result<T, Error> = from_str(content);
+  assert_eq!(loaded.unwrap(), model);
+}
+
+#[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+#[yaserde(root = "length")]
+pub struct Meters(f64);
+
+#[test]
+fn newtype_struct() {
+  convert_and_validate(
+    Meters(12.5),
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><length>12.5</length>",
+  );
+}
+
+#[test]
+fn newtype_struct_field() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "track")]
+  pub struct Track {
+    #[yaserde(attribute)]
+    width: Meters,
+    length: Meters,
+    laps: Vec<Meters>,
+  }
+
+  let model = Track {
+    width: Meters(7.5),
+    length: Meters(400.0),
+    laps: vec![Meters(1.5), Meters(2.0)],
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><track width=\"7.5\"><length>400</length><laps>1.5</laps><laps>2</laps></track>";
+  convert_and_validate(model, content);
+}
+
+#[test]
+fn tuple_struct() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "point")]
+  pub struct Point {
+    #[yaserde(attribute)]
+    x: i32,
+    #[yaserde(attribute)]
+    y: i32,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "segment")]
+  pub struct Segment(Point, Point);
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "pair")]
+  pub struct Pair(u32, u32);
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "series")]
+  pub struct Series(Vec<u32>, String);
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "range")]
+  pub struct Range(
+    #[yaserde(attribute, rename = "unit")] String,
+    #[yaserde(rename = "min")] Meters,
+    #[yaserde(rename = "max")] Option<Meters>,
+    #[yaserde(rename = "step")] Vec<Meters>,
+  );
+
+  convert_and_validate(
+    Segment(Point { x: 1, y: 2 }, Point { x: 3, y: 4 }),
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><segment><field0 x=\"1\" y=\"2\" /><field1 x=\"3\" y=\"4\" /></segment>",
+  );
+
+  convert_and_validate(
+    Pair(1, 2),
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><pair><field0>1</field0><field1>2</field1></pair>",
+  );
+
+  convert_and_validate(
+    Series(vec![1, 2], "end".to_string()),
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><series><field0>1</field0><field0>2</field0><field1>end</field1></series>",
+  );
+
+  convert_and_validate(
+    Range(
+      "m".to_string(),
+      Meters(1.0),
+      None,
+      vec![Meters(0.5), Meters(1.5)],
+    ),
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><range unit=\"m\"><min>1</min><step>0.5</step><step>1.5</step></range>",
+  );
+}
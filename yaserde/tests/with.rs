@@ -0,0 +1,113 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::io::{Read, Write};
+use yaserde::de::{from_str, Deserializer};
+use yaserde::ser::{to_string, Serializer};
+use yaserde::{Error, YaDeserialize, YaSerialize};
+
+mod yes_no {
+  use super::*;
+
+  pub fn serialize<W: Write>(value: &bool, writer: &mut Serializer<W>) -> Result<(), Error> {
+    writer.write_text_value(if *value { "Y" } else { "N" })
+  }
+
+  pub fn deserialize<R: Read>(reader: &mut Deserializer<R>) -> Result<bool, Error> {
+    reader.read_text(|text| match text.as_str() {
+      "Y" => Ok(true),
+      "N" => Ok(false),
+      _ => Err(Error::conversion(&text, "bool", "expected Y or N")),
+    })
+  }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Date {
+  year: u16,
+  month: u8,
+  day: u8,
+}
+
+fn write_date<W: Write>(date: &Date, writer: &mut Serializer<W>) -> Result<(), Error> {
+  let text = format!("{:04}{:02}{:02}", date.year, date.month, date.day);
+  writer.write_text_value(&text)
+}
+
+fn read_date<R: Read>(reader: &mut Deserializer<R>) -> Result<Date, Error> {
+  reader.read_text(|text| {
+    let parse = |range: std::ops::Range<usize>| {
+      text
+        .get(range)
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(|| Error::conversion(&text, "Date", "expected YYYYMMDD"))
+    };
+    Ok(Date {
+      year: parse(0..4)?,
+      month: parse(4..6)? as u8,
+      day: parse(6..8)? as u8,
+    })
+  })
+}
+
+#[test]
+fn with() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "book")]
+  pub struct Book {
+    #[yaserde(attribute, with = "yes_no")]
+    available: bool,
+    #[yaserde(serialize_with = "write_date", deserialize_with = "read_date")]
+    published: Date,
+    #[yaserde(
+      rename = "reprint",
+      serialize_with = "write_date",
+      deserialize_with = "read_date"
+    )]
+    reprints: Vec<Date>,
+    #[yaserde(with = "yes_no")]
+    illustrated: Option<bool>,
+  }
+
+  let model = Book {
+    available: true,
+    published: Date {
+      year: 1943,
+      month: 4,
+      day: 6,
+    },
+    reprints: vec![Date {
+      year: 1999,
+      month: 12,
+      day: 31,
+    }],
+    illustrated: Some(false),
+  };
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><book available=\"Y\"><published>19430406</published><reprint>19991231</reprint><illustrated>N</illustrated></book>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Book = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let content = "<book available=\"maybe\"><published>19430406</published></book>";
+  let error = from_str::<Book>(content).unwrap_err();
+  assert_eq!(error.path(), Some("/book/@available"));
+}
+
+#[test]
+fn with_newtype() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "published")]
+  pub struct Published(#[yaserde(with = "yes_no")] bool);
+
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><published>Y</published>";
+  assert_eq!(to_string(&Published(true)).unwrap(), content);
+
+  let loaded: Published = from_str(content).unwrap();
+  assert_eq!(loaded, Published(true));
+}
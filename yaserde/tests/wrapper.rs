@@ -0,0 +1,134 @@
+#[macro_use]
+extern crate log;
+extern crate xml;
+extern crate yaserde;
+#[macro_use]
+extern crate yaserde_derive;
+
+use std::collections::BTreeSet;
+use std::io::{Read, Write};
+use yaserde::de::from_str;
+use yaserde::ser::to_string;
+use yaserde::{YaDeserialize, YaSerialize};
+
+#[test]
+fn wrapped_sequence() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug, Default)]
+  #[yaserde(root = "dependency")]
+  pub struct Dependency {
+    #[yaserde(rename = "artifactId")]
+    artifact_id: String,
+    version: String,
+  }
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "project")]
+  pub struct Project {
+    #[yaserde(wrapper = "dependencies", item = "dependency")]
+    dependencies: Vec<Dependency>,
+    #[yaserde(wrapper = "modules", item = "module")]
+    modules: Option<BTreeSet<String>>,
+  }
+
+  let model = Project {
+    dependencies: vec![
+      Dependency {
+        artifact_id: "junit".to_string(),
+        version: "4.12".to_string(),
+      },
+      Dependency {
+        artifact_id: "log4j".to_string(),
+        version: "1.2".to_string(),
+      },
+    ],
+    modules: Some(vec!["core".to_string()].into_iter().collect()),
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><project><dependencies><dependency><artifactId>junit</artifactId><version>4.12</version></dependency><dependency><artifactId>log4j</artifactId><version>1.2</version></dependency></dependencies><modules><module>core</module></modules></project>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Project = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let loaded: Project = from_str("<project><dependency /></project>").unwrap();
+  assert_eq!(
+    loaded,
+    Project {
+      dependencies: vec![],
+      modules: None,
+    }
+  );
+
+  let model = Project {
+    dependencies: vec![],
+    modules: Some(BTreeSet::new()),
+  };
+  let content =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><project><dependencies /><modules /></project>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Project = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn optional_sequence_default_wrapper() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "post")]
+  pub struct Post {
+    #[yaserde(item = "tag")]
+    tags: Option<Vec<u32>>,
+    #[yaserde(rename = "note")]
+    notes: Option<Vec<String>>,
+  }
+
+  let model = Post {
+    tags: Some(vec![1, 2]),
+    notes: Some(vec!["a".to_string()]),
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><post><tags><tag>1</tag><tag>2</tag></tags><note><note>a</note></note></post>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Post = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  let model = Post {
+    tags: Some(vec![]),
+    notes: None,
+  };
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><post><tags /></post>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Post = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "pair")]
+  pub struct Pair(Option<Vec<u32>>, Option<Vec<u32>>);
+
+  let model = Pair(Some(vec![]), Some(vec![3]));
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><pair><field0 /><field1><field1>3</field1></field1></pair>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Pair = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
+
+#[test]
+fn wrapped_tuple_struct() {
+  #[derive(YaDeserialize, YaSerialize, PartialEq, Debug)]
+  #[yaserde(root = "manifest")]
+  pub struct Manifest(
+    #[yaserde(rename = "package")] String,
+    #[yaserde(wrapper = "permissions", item = "permission")] Vec<String>,
+  );
+
+  let model = Manifest(
+    "app".to_string(),
+    vec!["INTERNET".to_string(), "CAMERA".to_string()],
+  );
+  let content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><manifest><package>app</package><permissions><permission>INTERNET</permission><permission>CAMERA</permission></permissions></manifest>";
+  assert_eq!(to_string(&model).unwrap(), content);
+
+  let loaded: Manifest = from_str(content).unwrap();
+  assert_eq!(loaded, model);
+}
@@ -1,29 +1,69 @@
+use field_type::{get_field_type, FieldType};
 use proc_macro2::token_stream::IntoIter;
 use proc_macro2::Delimiter;
 use proc_macro2::TokenTree;
 use std::collections::BTreeMap;
+use std::iter::Peekable;
 use syn::Attribute;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct YaSerdeAttribute {
+  /// Other names accepted for the field on deserialization.
+  pub aliases: Vec<String>,
   pub root: Option<String>,
+  /// Where-predicates replacing the inferred bounds of the `YaSerialize` impl.
+  pub serialize_bound: Option<String>,
+  /// Where-predicates replacing the inferred bounds of the `YaDeserialize` impl.
+  pub deserialize_bound: Option<String>,
   pub rename: Option<String>,
+  pub rename_all: Option<String>,
   pub prefix: Option<String>,
   pub default: Option<String>,
+  pub deny_unknown_fields: bool,
+  /// `default` without function: fall back to `Default::default()`, or to the
+  /// variant carrying it.
+  pub use_default: bool,
   pub namespaces: BTreeMap<String, String>,
+  pub skip_serializing_if: Option<String>,
+  pub serialize_with: Option<String>,
+  pub deserialize_with: Option<String>,
+  pub skip_serializing: bool,
+  pub skip_deserializing: bool,
   pub attribute: bool,
+  pub flatten: bool,
+  /// Flattened sequence of mixed content, keeping the whitespace of its text.
+  /// Implies `flatten`, which does not need to be given as well.
+  pub mixed: bool,
+  pub from: Option<String>,
+  pub try_from: Option<String>,
+  pub into: Option<String>,
+  /// Name of the elements wrapping the items of nested sequences.
+  pub inner_wrapper: Option<String>,
+  /// Attribute carrying the key of each entry of a map written as repeated elements.
+  pub key_attribute: Option<String>,
+  /// Element wrapping the items of a sequence field.
+  pub wrapper: Option<String>,
+  /// Name of the items inside the `wrapper` element.
+  pub item: Option<String>,
+  /// Sequence written as a single `xs:list` value.
+  pub list: bool,
+  /// Separator of the items of a `list` field, whitespace by default.
+  pub separator: Option<String>,
   pub text: bool,
+  /// On an enum, write struct variants in an element named after the variant
+  /// instead of inline.
+  pub variant_element: bool,
 }
 
-fn get_value(iter: &mut IntoIter) -> Option<String> {
-  if let (Some(TokenTree::Punct(operator)), Some(TokenTree::Literal(value))) =
-    (iter.next(), iter.next())
-  {
-    if operator.as_char() == '=' {
-      Some(value.to_string().replace("\"", ""))
-    } else {
-      None
-    }
+fn get_value(iter: &mut Peekable<IntoIter>) -> Option<String> {
+  match iter.peek() {
+    Some(TokenTree::Punct(ref operator)) if operator.as_char() == '=' => {}
+    _ => return None,
+  }
+  iter.next();
+
+  if let Some(TokenTree::Literal(value)) = iter.next() {
+    Some(value.to_string().replace("\"", ""))
   } else {
     None
   }
@@ -31,55 +71,169 @@ fn get_value(iter: &mut IntoIter) -> Option<String> {
 
 impl YaSerdeAttribute {
   pub fn parse(attrs: &[Attribute]) -> YaSerdeAttribute {
+    let mut aliases = vec![];
     let mut attribute = false;
+    let mut serialize_bound = None;
+    let mut deserialize_bound = None;
+    let mut flatten = false;
+    let mut mixed = false;
+    let mut from = None;
+    let mut try_from = None;
+    let mut into = None;
+    let mut inner_wrapper = None;
+    let mut key_attribute = None;
+    let mut wrapper = None;
+    let mut item_name = None;
+    let mut list = false;
+    let mut separator = None;
     let mut namespaces = BTreeMap::new();
     let mut prefix = None;
     let mut rename = None;
+    let mut rename_all = None;
     let mut root = None;
+    let mut skip_serializing_if = None;
+    let mut serialize_with = None;
+    let mut deserialize_with = None;
+    let mut skip_serializing = false;
+    let mut skip_deserializing = false;
     let mut default = None;
+    let mut deny_unknown_fields = false;
+    let mut variant_element = false;
+    let mut use_default = false;
     let mut text = false;
 
     for attr in attrs.iter() {
       let mut attr_iter = attr.clone().tts.into_iter();
-      if let Some(token) = attr_iter.next() {
-        if let TokenTree::Group(group) = token {
-          if group.delimiter() == Delimiter::Parenthesis {
-            let mut attr_iter = group.stream().into_iter();
-
-            while let Some(item) = attr_iter.next() {
-              if let TokenTree::Ident(ident) = item {
-                match ident.to_string().as_str() {
-                  "attribute" => {
-                    attribute = true;
+      if let Some(TokenTree::Group(group)) = attr_iter.next() {
+        if group.delimiter() == Delimiter::Parenthesis {
+          let mut attr_iter = group.stream().into_iter().peekable();
+
+          while let Some(item) = attr_iter.next() {
+            if let TokenTree::Ident(ident) = item {
+              match ident.to_string().as_str() {
+                "alias" => {
+                  if let Some(alias) = get_value(&mut attr_iter) {
+                    aliases.push(alias);
                   }
-                  "namespace" => {
-                    if let Some(namespace) = get_value(&mut attr_iter) {
-                      let splitted: Vec<&str> = namespace.split(": ").collect();
-                      if splitted.len() == 2 {
-                        namespaces.insert(splitted[0].to_owned(), splitted[1].to_owned());
-                      }
-                      if splitted.len() == 1 {
-                        namespaces.insert("".to_owned(), splitted[0].to_owned());
+                }
+                "attribute" => {
+                  attribute = true;
+                }
+                "bound" => match attr_iter.peek() {
+                  Some(TokenTree::Group(ref group))
+                    if group.delimiter() == Delimiter::Parenthesis =>
+                  {
+                    let mut bound_iter = group.stream().into_iter().peekable();
+                    while let Some(item) = bound_iter.next() {
+                      if let TokenTree::Ident(ident) = item {
+                        match ident.to_string().as_str() {
+                          "serialize" => serialize_bound = get_value(&mut bound_iter),
+                          "deserialize" => deserialize_bound = get_value(&mut bound_iter),
+                          _ => {}
+                        }
                       }
                     }
                   }
-                  "prefix" => {
-                    prefix = get_value(&mut attr_iter);
-                  }
-                  "rename" => {
-                    rename = get_value(&mut attr_iter);
-                  }
-                  "root" => {
-                    root = get_value(&mut attr_iter);
+                  _ => {
+                    serialize_bound = get_value(&mut attr_iter);
+                    deserialize_bound = serialize_bound.clone();
                   }
-                  "default" => {
-                    default = get_value(&mut attr_iter);
+                },
+                "flatten" => {
+                  flatten = true;
+                }
+                "from" => {
+                  from = get_value(&mut attr_iter);
+                }
+                "inner_wrapper" => {
+                  inner_wrapper = get_value(&mut attr_iter);
+                }
+                "into" => {
+                  into = get_value(&mut attr_iter);
+                }
+                "item" => {
+                  item_name = get_value(&mut attr_iter);
+                }
+                "key_attribute" => {
+                  key_attribute = get_value(&mut attr_iter);
+                }
+                "list" => {
+                  list = true;
+                }
+                "mixed" => {
+                  mixed = true;
+                }
+                "namespace" => {
+                  if let Some(namespace) = get_value(&mut attr_iter) {
+                    let splitted: Vec<&str> = namespace.split(": ").collect();
+                    if splitted.len() == 2 {
+                      namespaces.insert(splitted[0].to_owned(), splitted[1].to_owned());
+                    }
+                    if splitted.len() == 1 {
+                      namespaces.insert("".to_owned(), splitted[0].to_owned());
+                    }
                   }
-                  "text" => {
-                    text = true;
+                }
+                "prefix" => {
+                  prefix = get_value(&mut attr_iter);
+                }
+                "rename" => {
+                  rename = get_value(&mut attr_iter);
+                }
+                "rename_all" => {
+                  rename_all = get_value(&mut attr_iter);
+                }
+                "root" => {
+                  root = get_value(&mut attr_iter);
+                }
+                "skip" => {
+                  skip_serializing = true;
+                  skip_deserializing = true;
+                }
+                "skip_deserializing" => {
+                  skip_deserializing = true;
+                }
+                "skip_serializing" => {
+                  skip_serializing = true;
+                }
+                "skip_serializing_if" => {
+                  skip_serializing_if = get_value(&mut attr_iter);
+                }
+                "deserialize_with" => {
+                  deserialize_with = get_value(&mut attr_iter);
+                }
+                "separator" => {
+                  separator = get_value(&mut attr_iter);
+                }
+                "serialize_with" => {
+                  serialize_with = get_value(&mut attr_iter);
+                }
+                "wrapper" => {
+                  wrapper = get_value(&mut attr_iter);
+                }
+                "with" => {
+                  if let Some(module) = get_value(&mut attr_iter) {
+                    serialize_with = Some(format!("{}::serialize", module));
+                    deserialize_with = Some(format!("{}::deserialize", module));
                   }
-                  _ => {}
                 }
+                "default" => match get_value(&mut attr_iter) {
+                  Some(value) => default = Some(value),
+                  None => use_default = true,
+                },
+                "deny_unknown_fields" => {
+                  deny_unknown_fields = true;
+                }
+                "try_from" => {
+                  try_from = get_value(&mut attr_iter);
+                }
+                "text" => {
+                  text = true;
+                }
+                "variant_element" => {
+                  variant_element = true;
+                }
+                _ => {}
               }
             }
           }
@@ -88,15 +242,79 @@ impl YaSerdeAttribute {
     }
 
     YaSerdeAttribute {
+      aliases,
       attribute,
+      serialize_bound,
+      deserialize_bound,
+      flatten: flatten || mixed,
+      mixed,
+      from,
+      try_from,
+      into,
+      inner_wrapper,
+      key_attribute,
+      wrapper,
+      item: item_name,
+      list,
+      separator,
       namespaces,
       prefix,
       rename,
+      rename_all,
       root,
+      skip_serializing_if,
+      serialize_with,
+      deserialize_with,
+      skip_serializing,
+      skip_deserializing,
       default,
+      deny_unknown_fields,
+      use_default,
       text,
+      variant_element,
     }
   }
+
+  /// Attributes of a field of a tuple struct or variant: unless renamed, its
+  /// elements are named after its position, `field0`, `field1`...
+  pub fn parse_unnamed(index: usize, field: &syn::Field) -> YaSerdeAttribute {
+    let mut field_attrs = YaSerdeAttribute::parse(&field.attrs);
+    if field.ident.is_none() && field_attrs.rename.is_none() && !field_attrs.attribute {
+      field_attrs.rename = Some(format!("field{}", index));
+    }
+    field_attrs
+  }
+
+  /// Name the `wrapper` of an `Option` of a sequence after the field when it
+  /// has none: written bare, `Some` of an empty sequence would be read back as
+  /// `None`.
+  pub fn with_default_wrapper(mut self, field: &syn::Field) -> YaSerdeAttribute {
+    let optional_sequence = match get_field_type(field) {
+      FieldType::Option { data_type } => matches!(*data_type, FieldType::Vec { .. }),
+      _ => false,
+    };
+
+    if optional_sequence
+      && self.wrapper.is_none()
+      && !(self.attribute || self.text || self.flatten || self.list)
+    {
+      self.wrapper = self
+        .rename
+        .clone()
+        .or_else(|| field.ident.as_ref().map(ToString::to_string));
+    }
+    self
+  }
+}
+
+/// Parse the type given to `attribute`.
+pub fn parse_type(attribute: &str, ty: &str) -> syn::Type {
+  syn::parse_str(ty).unwrap_or_else(|_| panic!("invalid yaserde {} type {:?}", attribute, ty))
+}
+
+/// Parse the path of a function given to `attribute`.
+pub fn parse_function_path(attribute: &str, path: &str) -> syn::ExprPath {
+  syn::parse_str(path).unwrap_or_else(|_| panic!("invalid {} path {:?}", attribute, path))
 }
 
 #[test]
@@ -106,13 +324,36 @@ fn parse_empty_attributes() {
 
   assert_eq!(
     YaSerdeAttribute {
+      aliases: vec![],
       root: None,
+      serialize_bound: None,
+      deserialize_bound: None,
       rename: None,
+      rename_all: None,
       prefix: None,
       default: None,
+      deny_unknown_fields: false,
+      use_default: false,
       namespaces: BTreeMap::new(),
+      skip_serializing_if: None,
+      serialize_with: None,
+      deserialize_with: None,
+      skip_serializing: false,
+      skip_deserializing: false,
       attribute: false,
+      flatten: false,
+      mixed: false,
+      from: None,
+      try_from: None,
+      into: None,
+      inner_wrapper: None,
+      key_attribute: None,
+      wrapper: None,
+      item: None,
+      list: false,
+      separator: None,
       text: false,
+      variant_element: false,
     },
     attrs
   );
@@ -153,13 +394,36 @@ fn parse_attributes() {
 
   assert_eq!(
     YaSerdeAttribute {
+      aliases: vec![],
       root: None,
+      serialize_bound: None,
+      deserialize_bound: None,
       rename: None,
+      rename_all: None,
       prefix: None,
       default: None,
+      deny_unknown_fields: false,
+      use_default: false,
       namespaces: BTreeMap::new(),
+      skip_serializing_if: None,
+      serialize_with: None,
+      deserialize_with: None,
+      skip_serializing: false,
+      skip_deserializing: false,
       attribute: true,
+      flatten: false,
+      mixed: false,
+      from: None,
+      try_from: None,
+      into: None,
+      inner_wrapper: None,
+      key_attribute: None,
+      wrapper: None,
+      item: None,
+      list: false,
+      separator: None,
       text: false,
+      variant_element: false,
     },
     attrs
   );
@@ -0,0 +1,120 @@
+use attribute::YaSerdeAttribute;
+use field_type::{get_field_type, FieldType};
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use syn;
+use syn::{Data, Generics, WhereClause, WherePredicate};
+
+/// Generics of a derived impl.
+///
+/// With `#[yaserde(bound = "...")]`, or the `serialize`/`deserialize` entry of
+/// `#[yaserde(bound(...))]` for this impl, the given predicates replace the
+/// inferred ones, otherwise every type parameter used by one of `types`, the
+/// types actually read or written, gets `bounds`.
+pub fn with_bound(
+  generics: &Generics,
+  types: &[syn::Type],
+  bound: &Option<String>,
+  bounds: &TokenStream,
+) -> Generics {
+  let predicates: Vec<WherePredicate> = match *bound {
+    Some(ref bound) => {
+      let where_clause: WhereClause = syn::parse_str(&format!("where {}", bound))
+        .unwrap_or_else(|_| panic!("invalid yaserde bound {:?}", bound));
+      where_clause.predicates.into_iter().collect()
+    }
+    None => generics
+      .type_params()
+      .filter(|param| {
+        let params = [param.ident.clone()];
+        types.iter().any(|ty| uses_params(quote!(#ty), &params))
+      })
+      .map(|param| {
+        let ident = &param.ident;
+        syn::parse2(quote!(#ident: #bounds)).expect("invalid inferred bound")
+      })
+      .collect(),
+  };
+
+  let mut generics = generics.clone();
+  generics.make_where_clause().predicates.extend(predicates);
+  generics
+}
+
+/// Generics of a derived `YaDeserialize` impl, with `Type: Default` for every
+/// field whose type involves a type parameter and which falls back to
+/// `Default::default()`: skipped fields and fields marked `default`, or all the
+/// fields of a struct marked `default`, without a `default` function. Nothing is
+/// added when a `bound` replaces the inferred ones.
+pub fn with_default_bound(
+  generics: &Generics,
+  data: &Data,
+  root_attrs: &YaSerdeAttribute,
+) -> Generics {
+  let mut generics = generics.clone();
+  if root_attrs.deserialize_bound.is_some() {
+    return generics;
+  }
+
+  let params: Vec<Ident> = generics
+    .type_params()
+    .map(|param| param.ident.clone())
+    .collect();
+  // on an enum, `default` marks the variant to fall back to instead
+  let struct_default = match *data {
+    Data::Struct(_) => root_attrs.use_default,
+    _ => false,
+  };
+  let predicates: Vec<WherePredicate> = fields(data)
+    .into_iter()
+    .filter(|field| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      let defaulted = match get_field_type(field) {
+        _ if field_attrs.skip_deserializing => true,
+        FieldType::Value => field_attrs.use_default || struct_default,
+        _ => false,
+      };
+      let ty = &field.ty;
+      defaulted && field_attrs.default.is_none() && uses_params(quote!(#ty), &params)
+    })
+    .map(|field| {
+      let ty = &field.ty;
+      syn::parse2(quote!(#ty: Default)).expect("invalid inferred bound")
+    })
+    .collect();
+
+  generics.make_where_clause().predicates.extend(predicates);
+  generics
+}
+
+/// Types of the fields of a struct or of the variants of an enum whose
+/// attributes satisfy `used`.
+pub fn field_types<F>(data: &Data, used: F) -> Vec<syn::Type>
+where
+  F: Fn(&YaSerdeAttribute) -> bool,
+{
+  fields(data)
+    .into_iter()
+    .filter(|field| used(&YaSerdeAttribute::parse(&field.attrs)))
+    .map(|field| field.ty.clone())
+    .collect()
+}
+
+fn fields(data: &Data) -> Vec<&syn::Field> {
+  match *data {
+    Data::Struct(ref data_struct) => data_struct.fields.iter().collect(),
+    Data::Enum(ref data_enum) => data_enum
+      .variants
+      .iter()
+      .flat_map(|variant| variant.fields.iter())
+      .collect(),
+    Data::Union(_) => vec![],
+  }
+}
+
+fn uses_params(tokens: TokenStream, params: &[Ident]) -> bool {
+  tokens.into_iter().any(|token| match token {
+    TokenTree::Ident(ref ident) => params.contains(ident),
+    TokenTree::Group(ref group) => uses_params(group.stream(), params),
+    _ => false,
+  })
+}
@@ -0,0 +1,156 @@
+use syn::{Data, DeriveInput, Field, Fields};
+
+use attribute::YaSerdeAttribute;
+
+/// Naming convention of `#[yaserde(rename_all = "...")]`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RenameRule {
+  Lower,
+  Camel,
+  Pascal,
+  Kebab,
+  ScreamingSnake,
+}
+
+impl RenameRule {
+  pub fn from_str(rule: &str) -> Result<RenameRule, String> {
+    match rule {
+      "lowercase" => Ok(RenameRule::Lower),
+      "camelCase" => Ok(RenameRule::Camel),
+      "PascalCase" => Ok(RenameRule::Pascal),
+      "kebab-case" => Ok(RenameRule::Kebab),
+      "SCREAMING_SNAKE_CASE" => Ok(RenameRule::ScreamingSnake),
+      _ => Err(format!(
+        "unknown rename_all rule {:?}, expected one of lowercase, camelCase, PascalCase, kebab-case, SCREAMING_SNAKE_CASE",
+        rule
+      )),
+    }
+  }
+
+  /// Rename a snake_case field.
+  pub fn apply_to_field(self, field: &str) -> String {
+    let field = field.trim_start_matches("r#");
+    match self {
+      RenameRule::Lower => field.to_string(),
+      RenameRule::Camel | RenameRule::Pascal => {
+        let mut capitalize = self == RenameRule::Pascal;
+        let mut renamed = String::new();
+        for c in field.chars() {
+          if c == '_' {
+            capitalize = !renamed.is_empty();
+          } else if capitalize {
+            renamed.extend(c.to_uppercase());
+            capitalize = false;
+          } else {
+            renamed.push(c);
+          }
+        }
+        renamed
+      }
+      RenameRule::Kebab => field.replace('_', "-"),
+      RenameRule::ScreamingSnake => field.to_uppercase(),
+    }
+  }
+
+  /// Rename a PascalCase variant.
+  pub fn apply_to_variant(self, variant: &str) -> String {
+    let variant = variant.trim_start_matches("r#");
+    let separated = |separator: char| {
+      let mut renamed = String::new();
+      for (index, c) in variant.char_indices() {
+        if index > 0 && c.is_uppercase() {
+          renamed.push(separator);
+        }
+        renamed.push(c);
+      }
+      renamed
+    };
+
+    match self {
+      RenameRule::Lower => variant.to_lowercase(),
+      RenameRule::Camel => {
+        let mut chars = variant.chars();
+        match chars.next() {
+          Some(first) => first.to_lowercase().chain(chars).collect(),
+          None => String::new(),
+        }
+      }
+      RenameRule::Pascal => variant.to_string(),
+      RenameRule::Kebab => separated('-').to_lowercase(),
+      RenameRule::ScreamingSnake => separated('_').to_uppercase(),
+    }
+  }
+}
+
+/// Apply the `rename_all` rule of the container by renaming every field and
+/// variant not renamed explicitly.
+pub fn apply_rename_all(
+  ast: &DeriveInput,
+  rename_all: &Option<String>,
+) -> Result<DeriveInput, String> {
+  let rule = match *rename_all {
+    Some(ref rule) => RenameRule::from_str(rule)?,
+    None => return Ok(ast.clone()),
+  };
+
+  let mut ast = ast.clone();
+  match ast.data {
+    Data::Struct(ref mut data_struct) => rename_fields(&mut data_struct.fields, rule),
+    Data::Enum(ref mut data_enum) => {
+      for variant in data_enum.variants.iter_mut() {
+        if YaSerdeAttribute::parse(&variant.attrs).rename.is_none() {
+          let renamed = rule.apply_to_variant(&variant.ident.to_string());
+          variant
+            .attrs
+            .push(parse_quote!(#[yaserde(rename = #renamed)]));
+        }
+        rename_fields(&mut variant.fields, rule);
+      }
+    }
+    Data::Union(_) => {}
+  }
+  Ok(ast)
+}
+
+fn rename_fields(fields: &mut Fields, rule: RenameRule) {
+  if let Fields::Named(ref mut fields) = *fields {
+    for field in fields.named.iter_mut() {
+      rename_field(field, rule);
+    }
+  }
+}
+
+fn rename_field(field: &mut Field, rule: RenameRule) {
+  if YaSerdeAttribute::parse(&field.attrs).rename.is_some() {
+    return;
+  }
+
+  if let Some(renamed) = field
+    .ident
+    .as_ref()
+    .map(|ident| rule.apply_to_field(&ident.to_string()))
+  {
+    field
+      .attrs
+      .push(parse_quote!(#[yaserde(rename = #renamed)]));
+  }
+}
+
+#[test]
+fn rename_fields_and_variants() {
+  let rules = [
+    ("lowercase", "user_name", "username"),
+    ("camelCase", "userName", "userName"),
+    ("PascalCase", "UserName", "UserName"),
+    ("kebab-case", "user-name", "user-name"),
+    ("SCREAMING_SNAKE_CASE", "USER_NAME", "USER_NAME"),
+  ];
+
+  for &(rule, field, variant) in rules.iter() {
+    let rule = RenameRule::from_str(rule).unwrap();
+    assert_eq!(rule.apply_to_field("user_name"), field);
+    assert_eq!(rule.apply_to_variant("UserName"), variant);
+  }
+
+  assert!(RenameRule::from_str("snake").is_err());
+}
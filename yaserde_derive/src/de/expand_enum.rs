@@ -1,250 +1,227 @@
 use attribute::*;
-use field_type::*;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::TokenStreamExt;
 use std::collections::BTreeMap;
 use syn::DataEnum;
 use syn::Fields;
+use syn::Generics;
 use syn::Ident;
-use de::build_default_value::build_default_value;
+
+use de::expand_struct::{build_struct, deserializer};
+use de::expand_tuple_struct::build_tuple_struct;
+use ser::expand_tuple_struct::is_newtype;
 
 pub fn parse(
   data_enum: &DataEnum,
   name: &Ident,
+  generics: &Generics,
   root: &str,
-  _namespaces: &BTreeMap<String, String>,
+  root_attrs: &YaSerdeAttribute,
 ) -> TokenStream {
-  let variables: TokenStream = data_enum
+  let variant_label_name = |variant: &syn::Variant| {
+    let field_attrs = YaSerdeAttribute::parse(&variant.attrs);
+    field_attrs
+      .rename
+      .unwrap_or_else(|| variant.ident.to_string())
+  };
+
+  // unit variants are matched on the text of the element, with their prefix
+  let unit_label_name = |variant: &syn::Variant| {
+    let field_attrs = YaSerdeAttribute::parse(&variant.attrs);
+    match field_attrs.prefix {
+      Some(prefix) => format!("{}:{}", prefix, variant_label_name(variant)),
+      None => variant_label_name(variant),
+    }
+  };
+
+  let expected_variants: Vec<String> = data_enum
     .variants
     .iter()
+    .filter(|variant| !YaSerdeAttribute::parse(&variant.attrs).text)
     .map(|variant| match variant.fields {
-      Fields::Unit => None,
-      Fields::Named(ref fields) => {
-        let enum_fields = fields
-          .named
-          .iter()
-          .map(|field| {
-            let field_label = &field.ident;
-            let field_attrs = YaSerdeAttribute::parse(&field.attrs);
-
-            match get_field_type(field) {
-              Some(FieldType::FieldTypeString) => {
-                build_default_value(field_label, &quote! {String}, &quote! {"".to_string()}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeBool) => {
-                build_default_value(field_label, &quote! {bool}, &quote! {false}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeI8) => {
-                build_default_value(field_label, &quote! {i8}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeU8) => {
-                build_default_value(field_label, &quote! {u8}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeI16) => {
-                build_default_value(field_label, &quote! {i16}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeU16) => {
-                build_default_value(field_label, &quote! {u16}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeI32) => {
-                build_default_value(field_label, &quote! {i32}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeU32) => {
-                build_default_value(field_label, &quote! {u32}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeI64) => {
-                build_default_value(field_label, &quote! {i64}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeU64) => {
-                build_default_value(field_label, &quote! {u64}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeF32) => {
-                build_default_value(field_label, &quote! {f32}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeF64) => {
-                build_default_value(field_label, &quote! {f64}, &quote! {0}, &field_attrs.default)
-              }
-              Some(FieldType::FieldTypeStruct { struct_name }) => build_default_value(
-                field_label,
-                &quote! {#struct_name},
-                &quote! {#struct_name::default()},
-                &field_attrs.default,
-              ),
-              Some(FieldType::FieldTypeOption { .. }) => {
-                if let Some(d) = &field_attrs.default {
-                  let default_function = Ident::new(&d, Span::call_site());
-
-                  Some(quote! {
-                    #[allow(unused_mut, non_snake_case, non_camel_case_types)]
-                    let mut #field_label = #default_function();
-                  })
-                } else {
-                  Some(quote! {
-                    #[allow(unused_mut, non_snake_case, non_camel_case_types)]
-                    let mut #field_label = None;
-                  })
-                }
-              }
-              Some(FieldType::FieldTypeVec { data_type }) => {
-                let dt = Box::into_raw(data_type);
-                match unsafe { dt.as_ref() } {
-                  Some(&FieldType::FieldTypeString) => {
-                    build_default_value(field_label, &quote! {Vec<String>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeBool) => {
-                    build_default_value(field_label, &quote! {Vec<bool>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeI8) => {
-                    build_default_value(field_label, &quote! {Vec<i8>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeU8) => {
-                    build_default_value(field_label, &quote! {Vec<u8>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeI16) => {
-                    build_default_value(field_label, &quote! {Vec<i16>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeU16) => {
-                    build_default_value(field_label, &quote! {Vec<u16>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeI32) => {
-                    build_default_value(field_label, &quote! {Vec<i32>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeU32) => {
-                    build_default_value(field_label, &quote! {Vec<u32>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeI64) => {
-                    build_default_value(field_label, &quote! {Vec<i64>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeU64) => {
-                    build_default_value(field_label, &quote! {Vec<u64>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeF32) => {
-                    build_default_value(field_label, &quote! {Vec<f32>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeF64) => {
-                    build_default_value(field_label, &quote! {Vec<f64>}, &quote! {vec![]}, &field_attrs.default)
-                  }
-                  Some(&FieldType::FieldTypeStruct { ref struct_name }) => build_default_value(
-                    field_label,
-                    &quote! {Vec<#struct_name>},
-                    &quote! {vec![]},
-                    &field_attrs.default,
-                  ),
-                  Some(&FieldType::FieldTypeOption { .. })
-                  | Some(&FieldType::FieldTypeVec { .. }) => {
-                    unimplemented!();
-                  }
-                  None => {
-                    unimplemented!();
-                  }
-                }
-              }
-              None => None,
+      Fields::Unit => unit_label_name(variant),
+      _ => variant_label_name(variant),
+    })
+    .collect();
+
+  let fallback = if root_attrs.use_default {
+    Some(quote!(Default::default()))
+  } else {
+    data_enum
+      .variants
+      .iter()
+      .find(|variant| YaSerdeAttribute::parse(&variant.attrs).use_default)
+      .map(|variant| {
+        let label = &variant.ident;
+        match variant.fields {
+          Fields::Unit => quote!(#name::#label),
+          _ => panic!("only unit variants can be the default of {}", name),
+        }
+      })
+  };
+
+  // text matching no unit variant is held by the `text` variant
+  let text_variant = data_enum
+    .variants
+    .iter()
+    .find(|variant| YaSerdeAttribute::parse(&variant.attrs).text)
+    .map(|variant| {
+      let label = &variant.ident;
+      match variant.fields {
+        Fields::Unnamed(ref fields) if is_newtype(&variant.fields) => {
+          let deserialize = deserializer(&YaSerdeAttribute::parse(&fields.unnamed[0].attrs));
+          quote! {
+            _ => {
+              enum_value = Some(#name::#label(reader.deserialize_text_with(&content, #deserialize)?));
             }
-          })
-          .filter(|x| x.is_some())
-          .map(|x| x.unwrap())
-          .fold(TokenStream::new(), |mut sum, val| {
-            sum.append_all(val);
-            sum
-          });
-
-        Some(enum_fields)
-      }
-      Fields::Unnamed(ref _fields) => {
-        unimplemented!();
+          }
+        }
+        _ => panic!("only newtype variants can be the text of {}", name),
       }
-    })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
-    .fold(TokenStream::new(), |mut sum, val| {
-      sum.append_all(val);
-      sum
     });
 
+  let (unknown_element, unknown_text, missing_variant) = match fallback {
+    Some(ref fallback) => (
+      quote!(_ => {
+        reader.skip_element()?;
+      }),
+      quote!(_ => {}),
+      fallback.clone(),
+    ),
+    None => (
+      quote!(found => return Err(unknown_variant(found)),),
+      quote!(found => return Err(unknown_variant(found)),),
+      quote!(return Err(unknown_variant(""))),
+    ),
+  };
+
+  let unknown_text = text_variant.unwrap_or(unknown_text);
+
   let match_to_enum: TokenStream = data_enum
     .variants
     .iter()
-    .map(|variant| {
-      let field_attrs = YaSerdeAttribute::parse(&variant.attrs);
-      let renamed_label = match field_attrs.rename {
-        Some(value) => Ident::new(&value.to_string(), Span::call_site()),
-        None => variant.ident.clone(),
-      };
+    .filter_map(|variant| {
       let label = &variant.ident;
-      let label_name = renamed_label.to_string();
+      let label_name = unit_label_name(variant);
 
       match variant.fields {
         Fields::Unit => Some(quote! {
           #label_name => {
-            simple_enum_value = Some(#name::#label);
+            enum_value = Some(#name::#label);
           }
         }),
         _ => None,
       }
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
     .fold(TokenStream::new(), |mut tokens, token| {
       tokens.append_all(token);
       tokens
     });
 
+  let call_visitors: TokenStream = data_enum
+    .variants
+    .iter()
+    .filter_map(|variant| {
+      let label = &variant.ident;
+      let label_name = variant_label_name(variant);
+      let no_namespace = BTreeMap::new();
+
+      let value = match variant.fields {
+        _ if YaSerdeAttribute::parse(&variant.attrs).text => return None,
+        Fields::Unit => return None,
+        Fields::Unnamed(ref fields) if is_newtype(&variant.fields) => {
+          let deserialize = deserializer(&YaSerdeAttribute::parse(&fields.unnamed[0].attrs));
+          quote!(#name::#label(#deserialize(reader)?))
+        }
+        Fields::Unnamed(_) => build_tuple_struct(
+          &variant.fields,
+          &quote!(#name::#label),
+          &label_name,
+          &no_namespace,
+          root_attrs.deny_unknown_fields,
+          false,
+        ),
+        Fields::Named(_) => build_struct(
+          &variant.fields,
+          &quote!(#name::#label),
+          &label_name,
+          &no_namespace,
+          root_attrs.deny_unknown_fields,
+          false,
+        ),
+      };
+
+      Some(quote! {
+        #label_name => {
+          enum_value = Some(#value);
+        }
+      })
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
   quote! {
     use xml::reader::XmlEvent;
 
-    impl YaDeserialize for #name {
+    impl #impl_generics YaDeserialize for #name #ty_generics #where_clause {
       #[allow(unused_variables)]
-      fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
+      fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, yaserde::Error> {
         let named_element =
-          if let XmlEvent::StartElement{name, ..} = reader.peek()?.to_owned() {
-            name.local_name.to_owned()
-          } else {
-            String::from(#root)
+          match reader.peek()?.to_owned() {
+            XmlEvent::StartElement{name, ..} => name,
+            event => {
+              return Err(yaserde::Error::UnexpectedEvent(format!("{:?}, expected <{}>", event, #root)))
+            }
           };
-        debug!("Enum: start to parse {:?}", named_element);
+        reader.next_event()?;
+        let depth = reader.depth();
+        debug!("Enum: start to parse {:?}", named_element.local_name);
 
         #[allow(unused_assignments, unused_mut)]
-        let mut simple_enum_value = None;
-
-        #variables
+        let mut enum_value = None;
+        let unknown_variant = |found: &str| yaserde::Error::UnknownVariant {
+          found: found.to_string(),
+          expected: &[#(#expected_variants),*],
+        };
 
         loop {
           match reader.peek()?.to_owned() {
-            XmlEvent::StartElement{name, attributes, namespace: _namespace} => {
-              debug!("Enum: {}: {}", named_element, name.local_name.as_str());
-              if name.local_name == named_element {
-                let _next = reader.next_event();
-
-                if let XmlEvent::Characters(content) = reader.peek()?.to_owned() {
-                  match content.as_str() {
-                    #match_to_enum
-                    _ => {}
-                  }
-                }
+            XmlEvent::StartElement{ref name, ..} => {
+              match name.local_name.as_str() {
+                #call_visitors
+                #unknown_element
               }
             },
-            XmlEvent::EndElement{name} => {
-              if name.local_name.as_str() == named_element {
+            XmlEvent::EndElement{..} => {
+              if reader.depth() == depth {
                 break;
               }
-              let _root = reader.next_event();
+              reader.next_event()?;
             },
-            xml::reader::XmlEvent::Characters(characters_content) => {
-              let _root = reader.next_event();
+            XmlEvent::Characters(content) => {
+              if reader.depth() == depth {
+                match content.as_str() {
+                  #match_to_enum
+                  #unknown_text
+                }
+              }
+              reader.next_event()?;
             },
             event => {
-              return Err(format!("unknown event {:?}", event))
+              return Err(yaserde::Error::UnexpectedEvent(format!("{:?}", event)))
             },
           }
         }
-
-        match simple_enum_value {
-          Some(value) => Ok(value),
-          None => {
-            Ok(#name::default())
-          },
-        }
+        let enum_value = match enum_value {
+          Some(value) => value,
+          None => #missing_variant,
+        };
+        reader.expect_end_element(&named_element)?;
+        Ok(enum_value)
       }
     }
   }
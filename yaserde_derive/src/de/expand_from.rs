@@ -0,0 +1,32 @@
+use attribute::parse_type;
+use proc_macro2::TokenStream;
+use syn::Generics;
+use syn::Ident;
+
+/// Deserialize the container as `from` (or `try_from` when `fallible`) and
+/// convert it.
+pub fn parse(name: &Ident, generics: &Generics, from: &str, fallible: bool) -> TokenStream {
+  let from = parse_type("from", from);
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  let convert = if fallible {
+    let target = name.to_string();
+    quote! {
+      <Self as ::std::convert::TryFrom<#from>>::try_from(value).map_err(|error| yaserde::Error::Invalid {
+        target: #target,
+        source: error.into(),
+      })
+    }
+  } else {
+    quote!(Ok(::std::convert::From::from(value)))
+  };
+
+  quote! {
+    impl #impl_generics YaDeserialize for #name #ty_generics #where_clause {
+      fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, yaserde::Error> {
+        let value: #from = yaserde::YaDeserialize::deserialize(reader)?;
+        #convert
+      }
+    }
+  }
+}
@@ -4,1342 +4,736 @@ use proc_macro2::{Span, TokenStream};
 use quote::TokenStreamExt;
 use std::collections::BTreeMap;
 use syn::DataStruct;
+use syn::Fields;
+use syn::Generics;
 use syn::Ident;
-use de::build_default_value::build_default_value;
 
 pub fn parse(
   data_struct: &DataStruct,
   name: &Ident,
+  generics: &Generics,
+  root: &str,
+  root_attrs: &YaSerdeAttribute,
+) -> TokenStream {
+  let build_struct = build_struct(
+    &data_struct.fields,
+    &quote!(#name),
+    root,
+    &root_attrs.namespaces,
+    root_attrs.deny_unknown_fields,
+    root_attrs.use_default,
+  );
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  quote! {
+    use xml::reader::XmlEvent;
+
+    impl #impl_generics YaDeserialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, yaserde::Error> {
+        Ok(#build_struct)
+      }
+    }
+  }
+}
+
+/// Expression reading the element at the reader position into `constructor`
+/// with named `fields`, for structs and struct variants. With `use_default`,
+/// every field absent from the element falls back to its default.
+pub fn build_struct(
+  fields: &Fields,
+  constructor: &TokenStream,
   root: &str,
   namespaces: &BTreeMap<String, String>,
+  deny_unknown_fields: bool,
+  use_default: bool,
 ) -> TokenStream {
   let validate_namespace: TokenStream = namespaces
-    .iter()
-    .map(|(_prefix, namespace)| {
-      Some(quote!(
+    .values()
+    .map(|namespace| {
+      quote!(
         let mut found = false;
         debug!("{:?}", namespace);
-        for (key, value) in namespace {
+        for (key, value) in &namespace {
           if #namespace == value {
             found = true;
           }
         }
         if !found {
-          return Err("bad namespace".to_string());
+          return Err(yaserde::Error::Namespace {
+            expected: #namespace.to_string(),
+          });
         }
-      ))
+      )
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
     .fold(TokenStream::new(), |mut tokens, token| {
       tokens.append_all(token);
       tokens
     });
 
-  let variables: TokenStream = data_struct
-    .fields
+  let variables: TokenStream = fields
     .iter()
     .map(|field| {
-      let label = &field.ident;
       let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      let variable = build_variable_name(&field.ident);
+      let field_type = &field.ty;
+      if field_attrs.skip_deserializing {
+        return TokenStream::new();
+      }
 
-      match get_field_type(field) {
-        Some(FieldType::FieldTypeString) => build_default_value(
-          label,
-          &quote! {String},
-          &quote! {"".to_string()},
-          &field_attrs.default,
-        ),
-        Some(FieldType::FieldTypeBool) => {
-          build_default_value(label, &quote! {bool}, &quote! {false}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeI8) => {
-          build_default_value(label, &quote! {i8}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeU8) => {
-          build_default_value(label, &quote! {u8}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeI16) => {
-          build_default_value(label, &quote! {i16}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeU16) => {
-          build_default_value(label, &quote! {u16}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeI32) => {
-          build_default_value(label, &quote! {i32}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeU32) => {
-          build_default_value(label, &quote! {u32}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeI64) => {
-          build_default_value(label, &quote! {i64}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeU64) => {
-          build_default_value(label, &quote! {u64}, &quote! {0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeF32) => {
-          build_default_value(label, &quote! {f32}, &quote! {0.0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeF64) => {
-          build_default_value(label, &quote! {f64}, &quote! {0.0}, &field_attrs.default)
-        }
-        Some(FieldType::FieldTypeStruct { struct_name }) => build_default_value(
-          label,
-          &quote! {#struct_name},
-          &quote! {#struct_name::default()},
-          &field_attrs.default,
-        ),
-        Some(FieldType::FieldTypeOption { .. }) => {
-          if let Some(d) = &field_attrs.default {
-            let default_function = Ident::new(&d, Span::call_site());
+      let init = build_default_value(&field_attrs);
 
-            Some(quote! {
-              #[allow(unused_mut, non_snake_case, non_camel_case_types)]
-              let mut #label = #default_function();
-            })
-          } else {
-            Some(quote! {
-              #[allow(unused_mut, non_snake_case, non_camel_case_types)]
-              let mut #label = None;
-            })
-          }
-        }
-        Some(FieldType::FieldTypeVec { data_type }) => {
-          let dt = Box::into_raw(data_type);
-          match unsafe { dt.as_ref() } {
-            Some(&FieldType::FieldTypeString) => build_default_value(
-              label,
-              &quote! {Vec<String>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeBool) => build_default_value(
-              label,
-              &quote! {Vec<bool>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeI8) => build_default_value(
-              label,
-              &quote! {Vec<i8>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeU8) => build_default_value(
-              label,
-              &quote! {Vec<u8>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeI16) => build_default_value(
-              label,
-              &quote! {Vec<i16>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeU16) => build_default_value(
-              label,
-              &quote! {Vec<u16>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeI32) => build_default_value(
-              label,
-              &quote! {Vec<i32>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeU32) => build_default_value(
-              label,
-              &quote! {Vec<u32>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeI64) => build_default_value(
-              label,
-              &quote! {Vec<i64>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeU64) => build_default_value(
-              label,
-              &quote! {Vec<u64>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeF32) => build_default_value(
-              label,
-              &quote! {Vec<f32>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeF64) => build_default_value(
-              label,
-              &quote! {Vec<f64>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeStruct { ref struct_name }) => build_default_value(
-              label,
-              &quote! {Vec<#struct_name>},
-              &quote! {vec![]},
-              &field_attrs.default,
-            ),
-            Some(&FieldType::FieldTypeOption { .. }) | Some(&FieldType::FieldTypeVec { .. }) => {
-              unimplemented!();
-            }
-            None => {
-              unimplemented!();
-            }
-          }
-        }
-        None => None,
+      match get_field_type(field) {
+        FieldType::Value => quote! {
+          #[allow(unused_mut)]
+          let mut #variable: Option<#field_type> = None;
+        },
+        _ => quote! {
+          #[allow(unused_mut)]
+          let mut #variable: #field_type = #init;
+        },
       }
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
-    .fold(TokenStream::new(), |mut sum, val| {
-      sum.append_all(val);
-      sum
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
     });
 
-  let field_visitors: TokenStream = data_struct
-    .fields
+  let attributes_loading: TokenStream = fields
     .iter()
-    .map(|field| {
+    .filter_map(|field| {
       let field_attrs = YaSerdeAttribute::parse(&field.attrs);
-      let label_name = if let Some(value) = field_attrs.rename {
-        Ident::new(&value.to_string(), Span::call_site()).to_string()
+      if field_attrs.skip_deserializing || !field_attrs.attribute {
+        return None;
+      }
+
+      let label_name = build_label_name(field, &field_attrs);
+      let variable = build_variable_name(&field.ident);
+      let field_type = get_field_type(field);
+      let action = if field_attrs.list {
+        let arguments = build_list_arguments(&field_attrs);
+        build_set_list_value(
+          &field_type,
+          &variable,
+          &quote!(reader.deserialize_attribute_list_with(attribute, #arguments)?),
+          &label_name,
+        )
       } else {
-        field.ident.clone().unwrap().to_string()
+        let deserialize = deserializer(&field_attrs);
+        build_set_value(
+          &field_type,
+          &variable,
+          &quote!(reader.deserialize_attribute_with(attribute, #deserialize)?),
+          &label_name,
+          &label_name,
+          false,
+        )
       };
 
-      let visitor_label = Ident::new(&format!("__Visitor{}", label_name), Span::call_site());
+      let aliases = &field_attrs.aliases;
 
-      match get_field_type(field) {
-        Some(FieldType::FieldTypeString) => {
-          build_declare_visitor(&quote! {String}, &quote! {visit_str}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeBool) => {
-          build_declare_visitor(&quote! {bool}, &quote! {visit_bool}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeI8) => {
-          build_declare_visitor(&quote! {i8}, &quote! {visit_i8}, &visitor_label)
+      Some(quote! {
+        #label_name #(| #aliases)* => {
+          #action
         }
-        Some(FieldType::FieldTypeU8) => {
-          build_declare_visitor(&quote! {u8}, &quote! {visit_u8}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeI16) => {
-          build_declare_visitor(&quote! {i16}, &quote! {visit_i16}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeU16) => {
-          build_declare_visitor(&quote! {u16}, &quote! {visit_u16}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeI32) => {
-          build_declare_visitor(&quote! {i32}, &quote! {visit_i32}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeU32) => {
-          build_declare_visitor(&quote! {u32}, &quote! {visit_u32}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeI64) => {
-          build_declare_visitor(&quote! {i64}, &quote! {visit_i64}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeU64) => {
-          build_declare_visitor(&quote! {u64}, &quote! {visit_u64}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeF32) => {
-          build_declare_visitor(&quote! {f32}, &quote! {visit_f32}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeF64) => {
-          build_declare_visitor(&quote! {f64}, &quote! {visit_f64}, &visitor_label)
-        }
-        Some(FieldType::FieldTypeStruct { struct_name }) => {
-          let struct_id = struct_name.to_string();
-          let struct_ident = Ident::new(
-            &format!("__Visitor_{}_{}", label_name, struct_name),
-            Span::call_site(),
-          );
-
-          Some(quote! {
-            #[allow(non_snake_case, non_camel_case_types)]
-            struct #struct_ident;
-            impl<'de> Visitor<'de> for #struct_ident {
-              type Value = #struct_name;
-
-              fn visit_str(self, v: &str) -> Result<Self::Value, String> {
-                let content = "<".to_string() + #struct_id + ">" + v + "</" + #struct_id + ">";
-                let value : Result<#struct_name, String> = yaserde::de::from_str(&content);
-                value
-              }
-            }
-          })
-        }
-        Some(FieldType::FieldTypeOption { data_type }) => {
-          let dt = Box::into_raw(data_type);
-          match unsafe { dt.as_ref() } {
-            Some(&FieldType::FieldTypeString) => {
-              build_declare_visitor(&quote! {String}, &quote! {visit_str}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeBool) => {
-              build_declare_visitor(&quote! {bool}, &quote! {visit_bool}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI8) => {
-              build_declare_visitor(&quote! {i8}, &quote! {visit_i8}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU8) => {
-              build_declare_visitor(&quote! {u8}, &quote! {visit_u8}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI16) => {
-              build_declare_visitor(&quote! {i16}, &quote! {visit_i16}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU16) => {
-              build_declare_visitor(&quote! {u16}, &quote! {visit_u16}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI32) => {
-              build_declare_visitor(&quote! {i32}, &quote! {visit_i32}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU32) => {
-              build_declare_visitor(&quote! {u32}, &quote! {visit_u32}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI64) => {
-              build_declare_visitor(&quote! {i64}, &quote! {visit_i64}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU64) => {
-              build_declare_visitor(&quote! {u64}, &quote! {visit_u64}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeF32) => {
-              build_declare_visitor(&quote! {f32}, &quote! {visit_f32}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeF64) => {
-              build_declare_visitor(&quote! {f64}, &quote! {visit_f64}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeStruct { ref struct_name }) => {
-              let struct_ident = Ident::new(&format!("{}", struct_name), Span::call_site());
-              Some(quote! {
-                #[allow(non_snake_case, non_camel_case_types)]
-                struct #visitor_label;
-                impl<'de> Visitor<'de> for #visitor_label {
-                  type Value = #struct_ident;
-                }
-              })
-            }
-            dude => {
-              println!("{:?}", dude);
-              unimplemented!();
-            }
-          }
-        }
-        Some(FieldType::FieldTypeVec { data_type }) => {
-          let dt = Box::into_raw(data_type);
-          match unsafe { dt.as_ref() } {
-            Some(&FieldType::FieldTypeString) => {
-              build_declare_visitor(&quote! {String}, &quote! {visit_str}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeBool) => {
-              build_declare_visitor(&quote! {bool}, &quote! {visit_bool}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI8) => {
-              build_declare_visitor(&quote! {i8}, &quote! {visit_i8}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU8) => {
-              build_declare_visitor(&quote! {u8}, &quote! {visit_u8}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI16) => {
-              build_declare_visitor(&quote! {i16}, &quote! {visit_i16}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU16) => {
-              build_declare_visitor(&quote! {u16}, &quote! {visit_u16}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI32) => {
-              build_declare_visitor(&quote! {i32}, &quote! {visit_i32}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU32) => {
-              build_declare_visitor(&quote! {u32}, &quote! {visit_u32}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeI64) => {
-              build_declare_visitor(&quote! {i64}, &quote! {visit_i64}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeU64) => {
-              build_declare_visitor(&quote! {u64}, &quote! {visit_u64}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeF32) => {
-              build_declare_visitor(&quote! {f32}, &quote! {visit_f32}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeF64) => {
-              build_declare_visitor(&quote! {f64}, &quote! {visit_f64}, &visitor_label)
-            }
-            Some(&FieldType::FieldTypeStruct { ref struct_name }) => {
-              let struct_ident = Ident::new(&format!("{}", struct_name), Span::call_site());
-              Some(quote! {
-                #[allow(non_snake_case, non_camel_case_types)]
-                struct #visitor_label;
-                impl<'de> Visitor<'de> for #visitor_label {
-                  type Value = #struct_ident;
-                }
-              })
-            }
-            _ => None,
-          }
-        }
-        None => None,
-      }
+      })
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
-    .fold(TokenStream::new(), |mut sum, val| {
-      sum.append_all(val);
-      sum
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
     });
 
-  let call_visitors: TokenStream = data_struct
-    .fields
+  let call_visitors: TokenStream = fields
     .iter()
-    .map(|field| {
-      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
-      let label = &field.ident;
-
-      if field_attrs.attribute {
+    .filter_map(|field| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs).with_default_wrapper(field);
+      if field_attrs.skip_deserializing
+        || field_attrs.attribute
+        || field_attrs.text
+        || field_attrs.flatten
+      {
         return None;
       }
 
-      let label_name = if let Some(value) = field_attrs.rename {
-        Ident::new(&value.to_string(), Span::call_site()).to_string()
-      } else {
-        field.ident.clone().unwrap().to_string()
-      };
+      let label_name = build_label_name(field, &field_attrs);
+      let variable = build_variable_name(&field.ident);
+      let field_type = get_field_type(field);
+      let element_name = build_element_name(&field_type, &field_attrs, &label_name);
+      let action = build_set_element_value(&field_type, &field_attrs, &variable, &label_name);
 
-      let visitor_label = Ident::new(&format!("__Visitor{}", label_name), Span::call_site());
+      let aliases = &field_attrs.aliases;
 
-      match get_field_type(field) {
-        Some(FieldType::FieldTypeString) => {
-          let visitor = Ident::new("visit_str", Span::call_site());
-          build_call_visitor(
-            &quote! {String},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeBool) => {
-          let visitor = Ident::new("visit_bool", Span::call_site());
-          build_call_visitor(
-            &quote! {bool},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeI8) => {
-          let visitor = Ident::new("visit_i8", Span::call_site());
-          build_call_visitor(
-            &quote! {i8},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeU8) => {
-          let visitor = Ident::new("visit_u8", Span::call_site());
-          build_call_visitor(
-            &quote! {u8},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
+      Some(quote! {
+        #element_name #(| #aliases)* => {
+          #action
         }
-        Some(FieldType::FieldTypeU16) => {
-          let visitor = Ident::new("visit_u16", Span::call_site());
-          build_call_visitor(
-            &quote! {u16},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeI16) => {
-          let visitor = Ident::new("visit_i16", Span::call_site());
-          build_call_visitor(
-            &quote! {i16},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeU32) => {
-          let visitor = Ident::new("visit_u32", Span::call_site());
-          build_call_visitor(
-            &quote! {u32},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeI32) => {
-          let visitor = Ident::new("visit_i32", Span::call_site());
-          build_call_visitor(
-            &quote! {i32},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeU64) => {
-          let visitor = Ident::new("visit_u64", Span::call_site());
-          build_call_visitor(
-            &quote! {u64},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeI64) => {
-          let visitor = Ident::new("visit_i64", Span::call_site());
-          build_call_visitor(
-            &quote! {i64},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeF32) => {
-          let visitor = Ident::new("visit_f32", Span::call_site());
-          build_call_visitor(
-            &quote! {f32},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeF64) => {
-          let visitor = Ident::new("visit_f64", Span::call_site());
-          build_call_visitor(
-            &quote! {f64},
-            &visitor,
-            &quote! {= value},
-            &visitor_label,
-            label,
-            &label_name,
-          )
-        }
-        Some(FieldType::FieldTypeStruct { struct_name }) => Some(quote! {
-          #label_name => {
-            reader.set_map_value();
-            match #struct_name::deserialize(reader) {
-              Ok(parsed_item) => {
-                #label = parsed_item;
-                let _root = reader.next_event();
-              },
-              Err(msg) => {
-                return Err(msg);
-              },
-            }
-          }
-        }),
-        Some(FieldType::FieldTypeOption { data_type }) => {
-          let dt = Box::into_raw(data_type);
-          match unsafe { dt.as_ref() } {
-            Some(&FieldType::FieldTypeString) => {
-              let visitor = Ident::new("visit_str", Span::call_site());
-              build_call_visitor(
-                &quote! {String},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeBool) => {
-              let visitor = Ident::new("visit_bool", Span::call_site());
-              build_call_visitor(
-                &quote! {bool},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU8) => {
-              let visitor = Ident::new("visit_u8", Span::call_site());
-              build_call_visitor(
-                &quote! {u8},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI8) => {
-              let visitor = Ident::new("visit_i8", Span::call_site());
-              build_call_visitor(
-                &quote! {i8},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU16) => {
-              let visitor = Ident::new("visit_u16", Span::call_site());
-              build_call_visitor(
-                &quote! {u16},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI16) => {
-              let visitor = Ident::new("visit_i16", Span::call_site());
-              build_call_visitor(
-                &quote! {i16},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU32) => {
-              let visitor = Ident::new("visit_u32", Span::call_site());
-              build_call_visitor(
-                &quote! {u32},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI32) => {
-              let visitor = Ident::new("visit_i32", Span::call_site());
-              build_call_visitor(
-                &quote! {i32},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU64) => {
-              let visitor = Ident::new("visit_u64", Span::call_site());
-              build_call_visitor(
-                &quote! {u64},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI64) => {
-              let visitor = Ident::new("visit_i64", Span::call_site());
-              build_call_visitor(
-                &quote! {i64},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeF32) => {
-              let visitor = Ident::new("visit_f32", Span::call_site());
-              build_call_visitor(
-                &quote! {f32},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeF64) => {
-              let visitor = Ident::new("visit_f64", Span::call_site());
-              build_call_visitor(
-                &quote! {f64},
-                &visitor,
-                &quote! {= Some(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeStruct { ref struct_name }) => {
-              let struct_ident = Ident::new(&format!("{}", struct_name), Span::call_site());
-              Some(quote! {
-                #label_name => {
-                  reader.set_map_value();
-                  match #struct_ident::deserialize(reader) {
-                    Ok(parsed_item) => {
-                      #label = Some(parsed_item);
-                      let _root = reader.next_event();
-                    },
-                    Err(msg) => {
-                      return Err(msg);
-                    },
-                  }
-                }
-              })
-            }
-            _ => unimplemented!(),
-          }
-        }
-        Some(FieldType::FieldTypeVec { data_type }) => {
-          let dt = Box::into_raw(data_type);
-          match unsafe { dt.as_ref() } {
-            Some(&FieldType::FieldTypeString) => {
-              let visitor = Ident::new("visit_str", Span::call_site());
-              build_call_visitor(
-                &quote! {String},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeBool) => {
-              let visitor = Ident::new("visit_bool", Span::call_site());
-              build_call_visitor(
-                &quote! {bool},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI8) => {
-              let visitor = Ident::new("visit_i8", Span::call_site());
-              build_call_visitor(
-                &quote! {i8},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU8) => {
-              let visitor = Ident::new("visit_u8", Span::call_site());
-              build_call_visitor(
-                &quote! {u8},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI16) => {
-              let visitor = Ident::new("visit_i16", Span::call_site());
-              build_call_visitor(
-                &quote! {i16},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU16) => {
-              let visitor = Ident::new("visit_u16", Span::call_site());
-              build_call_visitor(
-                &quote! {u16},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI32) => {
-              let visitor = Ident::new("visit_i32", Span::call_site());
-              build_call_visitor(
-                &quote! {i32},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU32) => {
-              let visitor = Ident::new("visit_u32", Span::call_site());
-              build_call_visitor(
-                &quote! {u32},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeI64) => {
-              let visitor = Ident::new("visit_i64", Span::call_site());
-              build_call_visitor(
-                &quote! {i64},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeU64) => {
-              let visitor = Ident::new("visit_u64", Span::call_site());
-              build_call_visitor(
-                &quote! {u64},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeF32) => {
-              let visitor = Ident::new("visit_f32", Span::call_site());
-              build_call_visitor(
-                &quote! {f32},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeF64) => {
-              let visitor = Ident::new("visit_f64", Span::call_site());
-              build_call_visitor(
-                &quote! {f64},
-                &visitor,
-                &quote! {.push(value)},
-                &visitor_label,
-                label,
-                &label_name,
-              )
-            }
-            Some(&FieldType::FieldTypeStruct { ref struct_name }) => {
-              let struct_ident = Ident::new(&format!("{}", struct_name), Span::call_site());
-              Some(quote! {
-                #label_name => {
-                  reader.set_map_value();
-                  match #struct_ident::deserialize(reader) {
-                    Ok(parsed_item) => {
-                      #label.push(parsed_item);
-                      let _root = reader.next_event();
-                    },
-                    Err(msg) => {
-                      return Err(msg);
-                    },
-                  }
-                }
-              })
-            }
-            _ => unimplemented!(),
-          }
-        }
-        None => None,
-      }
+      })
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
-    .fold(TokenStream::new(), |mut sum, val| {
-      sum.append_all(val);
-      sum
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
     });
 
-  let attributes_loading: TokenStream = data_struct
-    .fields
+  let set_text: TokenStream = fields
     .iter()
-    .map(|field| {
+    .filter_map(|field| {
       let field_attrs = YaSerdeAttribute::parse(&field.attrs);
-      if !field_attrs.attribute {
+      if field_attrs.skip_deserializing || !field_attrs.text {
         return None;
       }
 
-      let label = &field.ident;
-      let label_name = if let Some(value) = field_attrs.rename {
-        Ident::new(&value.to_string(), Span::call_site()).to_string()
-      } else {
-        field.ident.clone().unwrap().to_string()
-      };
-
-      let visitor_label = Ident::new(&format!("__Visitor{}", label_name), Span::call_site());
-
-      match get_field_type(field) {
-        Some(FieldType::FieldTypeString) => Some(quote! {
-          for attr in attributes {
-            if attr.name.local_name == #label_name {
-              #label = attr.value.to_owned();
-            }
-          }
-        }),
-        Some(FieldType::FieldTypeBool) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_bool},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeI8) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_i8},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeU8) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_u8},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeI16) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_i16},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeU16) => build_call_visitor_for_attribute(
-          label,
+      let label_name = build_label_name(field, &field_attrs);
+      let variable = build_variable_name(&field.ident);
+      let field_type = get_field_type(field);
+      if field_attrs.list {
+        let arguments = build_list_arguments(&field_attrs);
+        return Some(build_set_list_value(
+          &field_type,
+          &variable,
+          &quote!(reader.deserialize_text_list_with(&text_content, #arguments)?),
           &label_name,
-          &quote! {= value},
-          &quote! {visit_u16},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeI32) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_i32},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeU32) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_u32},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeI64) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_i64},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeU64) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_u64},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeF32) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_f32},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeF64) => build_call_visitor_for_attribute(
-          label,
-          &label_name,
-          &quote! {= value},
-          &quote! {visit_f64},
-          &visitor_label,
-        ),
-        Some(FieldType::FieldTypeOption { data_type }) => {
-          let dt = Box::into_raw(data_type);
-          match unsafe { dt.as_ref() } {
-            Some(&FieldType::FieldTypeString) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_str},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeBool) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_bool},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeU8) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_u8},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeI8) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_i8},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeU16) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_u16},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeI16) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_i16},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeU32) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_u32},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeI32) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_i32},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeU64) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_u64},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeI64) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_i64},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeF32) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_f32},
-              &visitor_label,
-            ),
-            Some(&FieldType::FieldTypeF64) => build_call_visitor_for_attribute(
-              label,
-              &label_name,
-              &quote! {= Some(value)},
-              &quote! {visit_f64},
-              &visitor_label,
-            ),
-            _ => None,
-          }
-        }
-        Some(FieldType::FieldTypeStruct { struct_name }) => {
-          let struct_ident = Ident::new(
-            &format!("__Visitor_{}_{}", label_name, struct_name),
-            Span::call_site(),
-          );
-
-          Some(quote! {
-            for attr in attributes {
-              if attr.name.local_name == #label_name {
-                let visitor = #struct_ident{};
-                match visitor.visit_str(&attr.value) {
-                  Ok(value) => {#label = value;}
-                  Err(msg) => {return Err(msg);}
-                }
-              }
-            }
-          })
-        }
-        _ => None,
+        ));
       }
+
+      let deserialize = deserializer(&field_attrs);
+      Some(build_set_value(
+        &field_type,
+        &variable,
+        &quote!(reader.deserialize_text_with(&text_content, #deserialize)?),
+        &label_name,
+        &label_name,
+        false,
+      ))
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
-    .fold(TokenStream::new(), |mut sum, val| {
-      sum.append_all(val);
-      sum
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
     });
 
-  let set_text: TokenStream = data_struct
-    .fields
+  // flattened fields are read from the attributes of the element and from the
+  // children and text no other field matched, replayed once the element is read
+  let set_flattened: TokenStream = fields
     .iter()
-    .map(|field| {
-      let label = &field.ident;
+    .filter_map(|field| {
       let field_attrs = YaSerdeAttribute::parse(&field.attrs);
-
-      match get_field_type(field) {
-        Some(FieldType::FieldTypeString) => {
-          build_set_text_to_value(&field_attrs, label, &quote! {text_content.to_owned()})
-        }
-        Some(FieldType::FieldTypeBool) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {bool::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeI8) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {i8::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeU8) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {u8::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeI16) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {i16::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeU16) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {u16::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeI32) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {i32::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeU32) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {u32::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeI64) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {i64::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeU64) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {u64::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeF32) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {f32::from_str(text_content).unwrap()},
-        ),
-        Some(FieldType::FieldTypeF64) => build_set_text_to_value(
-          &field_attrs,
-          label,
-          &quote! {f64::from_str(text_content).unwrap()},
-        ),
-
-        Some(FieldType::FieldTypeStruct { .. })
-        | Some(FieldType::FieldTypeOption { .. })
-        | Some(FieldType::FieldTypeVec { .. })
-        | None => None,
+      if field_attrs.skip_deserializing || !field_attrs.flatten {
+        return None;
       }
+
+      let label_name = build_label_name(field, &field_attrs);
+      let variable = build_variable_name(&field.ident);
+      let value = quote!(reader.deserialize_flattened(&start_event, &flattened.concat())?);
+      Some(match get_field_type(field) {
+        FieldType::Value => quote! {
+          #variable = Some(#value);
+        },
+        FieldType::Option { ref data_type } => match **data_type {
+          FieldType::Value => quote! {
+            if flattened_attributes || !flattened.iter().all(|events| yaserde::de::is_blank(events)) {
+              #variable = Some(#value);
+            }
+          },
+          _ => unsupported(&label_name),
+        },
+        FieldType::Vec {
+          ref data_type,
+          sequence,
+        } => match **data_type {
+          FieldType::Value => {
+            let push = sequence.push();
+            // whitespace between the children only makes an item of mixed content
+            let filter = if field_attrs.mixed {
+              None
+            } else {
+              Some(quote!(.filter(|events| !yaserde::de::is_blank(events))))
+            };
+            quote! {
+              for events in flattened.iter()#filter {
+                #variable.#push(reader.deserialize_flattened(&start_event, events)?);
+              }
+            }
+          }
+          _ => unsupported(&label_name),
+        },
+        FieldType::Map => unsupported(&label_name),
+      })
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
     .fold(TokenStream::new(), |mut tokens, token| {
       tokens.append_all(token);
       tokens
     });
+  let has_flattened = !set_flattened.is_empty();
+  if deny_unknown_fields && has_flattened {
+    panic!("deny_unknown_fields cannot be combined with flatten");
+  }
 
-  let struct_builder: TokenStream = data_struct
-    .fields
+  let struct_builder: TokenStream = fields
     .iter()
     .map(|field| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
       let label = &field.ident;
+      let label_name = build_label_name(field, &field_attrs);
+      let variable = build_variable_name(label);
+
+      let value = match get_field_type(field) {
+        _ if field_attrs.skip_deserializing => build_default_value(&field_attrs),
+        FieldType::Value => {
+          if let Some(ref d) = field_attrs.default {
+            let default_function = Ident::new(d, Span::call_site());
+            quote!(#variable.unwrap_or_else(#default_function))
+          } else if field_attrs.use_default || use_default {
+            quote!(#variable.unwrap_or_default())
+          } else if field_attrs.text {
+            let deserialize = deserializer(&field_attrs);
+            quote! {
+              match #variable {
+                Some(value) => value,
+                None => reader.deserialize_text_with("", #deserialize)?,
+              }
+            }
+          } else if field_attrs.attribute {
+            quote! {
+              #variable.ok_or_else(|| yaserde::Error::MissingAttribute(#label_name.to_string()))?
+            }
+          } else {
+            quote! {
+              #variable.ok_or_else(|| yaserde::Error::MissingElement(#label_name.to_string()))?
+            }
+          }
+        }
+        _ => quote!(#variable),
+      };
 
-      if get_field_type(field).is_some() {
-        Some(quote! {
-          #label: #label,
-        })
-      } else {
-        None
+      quote! {
+        #label: #value,
       }
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
     .fold(TokenStream::new(), |mut tokens, token| {
       tokens.append_all(token);
       tokens
     });
 
-  quote! {
-    use xml::reader::XmlEvent;
-    use yaserde::Visitor;
-    #[allow(unknown_lints, unused_imports)]
-    use std::str::FromStr;
+  let unknown_attribute = if deny_unknown_fields {
+    quote!(return Err(yaserde::Error::UnknownAttribute(attribute.name.local_name.clone()));)
+  } else if has_flattened {
+    quote!(flattened_attributes = true;)
+  } else {
+    TokenStream::new()
+  };
 
-    impl YaDeserialize for #name {
-      #[allow(unused_variables)]
-      fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
-        let named_element =
-          if let XmlEvent::StartElement{name, ..} = reader.peek()?.to_owned() {
-            name.local_name.to_owned()
-          } else {
-            String::from(#root)
-          };
-        debug!("Struct: start to parse {:?}", named_element);
+  let load_attributes = if attributes_loading.is_empty() && !deny_unknown_fields && !has_flattened {
+    None
+  } else {
+    Some(quote! {
+      for attribute in &attributes {
+        match attribute.name.local_name.as_str() {
+          #attributes_loading
+          _ => {
+            #unknown_attribute
+          }
+        }
+      }
+    })
+  };
 
-        #variables
-        #field_visitors
+  let read_text = if set_text.is_empty() && has_flattened {
+    quote! {
+      XmlEvent::Characters(_) => {
+        flattened.push(vec![reader.next_event()?]);
+      }
+    }
+  } else if set_text.is_empty() {
+    quote! {
+      XmlEvent::Characters(_) => {
+        reader.next_event()?;
+      }
+    }
+  } else {
+    quote! {
+      XmlEvent::Characters(text_content) => {
+        reader.next_event()?;
+        #set_text
+      }
+    }
+  };
 
-        loop {
-          match reader.peek()?.to_owned() {
-            XmlEvent::StartElement{ref name, ref attributes, ref namespace} => {
-              #validate_namespace
+  let skip_unknown = if has_flattened {
+    quote!(flattened.push(reader.collect_element()?);)
+  } else if deny_unknown_fields {
+    quote!(return Err(yaserde::Error::UnknownElement(name.local_name.clone()));)
+  } else {
+    quote!(reader.skip_element()?;)
+  };
 
-              match name.local_name.as_str() {
-                #call_visitors
-                named_element => {
-                  let _root = reader.next_event();
-                }
-                // name => {
-                //   return Err(format!("unknown key {}", name))
-                // }
-              }
-              #attributes_loading
-            }
-            XmlEvent::EndElement{ref name} => {
-              if name.local_name == named_element {
-                break;
-              }
-              let _root = reader.next_event();
-            }
-            XmlEvent::Characters(ref text_content) => {
-              #set_text
-              let _root = reader.next_event();
-            }
-            event => {
-              return Err(format!("unknown event {:?}", event))
+  // the text replayed to flattened fields is trimmed by the fields themselves
+  let preserve_whitespace = if fields
+    .iter()
+    .any(|field| YaSerdeAttribute::parse(&field.attrs).mixed)
+  {
+    Some(quote!(reader.preserve_whitespace();))
+  } else if set_text.is_empty() && has_flattened {
+    Some(quote!(reader.preserve_text_whitespace();))
+  } else {
+    None
+  };
+
+  let declare_flattened = if has_flattened {
+    Some(quote! {
+      let mut flattened: Vec<Vec<XmlEvent>> = vec![];
+      let mut flattened_attributes = false;
+    })
+  } else {
+    None
+  };
+
+  quote! {{
+    let (named_element, attributes) =
+      match reader.peek()?.to_owned() {
+        XmlEvent::StartElement{name, attributes, namespace} => {
+          #validate_namespace
+          (name, attributes)
+        }
+        event => {
+          return Err(yaserde::Error::UnexpectedEvent(format!("{:?}, expected <{}>", event, #root)))
+        }
+      };
+    let start_event = reader.next_event()?;
+    #preserve_whitespace
+    let depth = reader.depth();
+    debug!("Struct: start to parse {:?}", named_element.local_name);
+
+    #variables
+    #declare_flattened
+    #load_attributes
+
+    loop {
+      match reader.peek()?.to_owned() {
+        XmlEvent::StartElement{ref name, ..} => {
+          match name.local_name.as_str() {
+            #call_visitors
+            _ => {
+              #skip_unknown
             }
           }
         }
-
-        Ok(#name{#struct_builder})
+        XmlEvent::EndElement{..} => {
+          if reader.depth() == depth {
+            break;
+          }
+          reader.next_event()?;
+        }
+        #read_text
+        event => {
+          return Err(yaserde::Error::UnexpectedEvent(format!("{:?}", event)))
+        }
       }
     }
+    #set_flattened
+    let value = #constructor{#struct_builder};
+    reader.expect_end_element(&named_element)?;
+    value
+  }}
+}
+
+/// Name of the element or attribute matched for a field.
+pub fn build_label_name(field: &syn::Field, field_attrs: &YaSerdeAttribute) -> String {
+  match field_attrs.rename {
+    Some(ref value) => value.to_string(),
+    None => field.ident.as_ref().unwrap().to_string(),
   }
 }
 
-fn build_declare_visitor(
-  field_type: &TokenStream,
-  visitor: &TokenStream,
-  visitor_label: &Ident,
-) -> Option<TokenStream> {
-  Some(quote! {
-    #[allow(non_snake_case, non_camel_case_types)]
-    struct #visitor_label;
-    impl<'de> Visitor<'de> for #visitor_label {
-      type Value = #field_type;
+/// Local variable accumulating the value of a field while its element is read.
+pub fn build_variable_name(label: &Option<Ident>) -> Ident {
+  let label = label.as_ref().unwrap().to_string();
+  Ident::new(
+    &format!("__{}", label.trim_start_matches("r#")),
+    Span::call_site(),
+  )
+}
 
-      fn #visitor(self, v: &str) -> Result<Self::Value, String> {
-        Ok(#field_type::from_str(v).unwrap())
+/// Function reading the items of a field, its `deserialize_with` function or
+/// `YaDeserialize::deserialize`.
+pub fn deserializer(field_attrs: &YaSerdeAttribute) -> TokenStream {
+  match field_attrs.deserialize_with {
+    Some(ref path) => {
+      let path = parse_function_path("deserialize_with", path);
+      quote!(#path)
+    }
+    None => quote!(yaserde::YaDeserialize::deserialize),
+  }
+}
+
+/// Value read from a child element of a field. The element of a map entry
+/// carrying its key in `key_attribute` gives a map holding that entry.
+fn build_element_value(field_attrs: &YaSerdeAttribute) -> TokenStream {
+  let deserialize = deserializer(field_attrs);
+  match field_attrs.key_attribute {
+    Some(ref key_attribute) => quote! {{
+      let yaserde_key = match *reader.peek()? {
+        XmlEvent::StartElement { ref attributes, .. } => attributes
+          .iter()
+          .find(|attribute| attribute.name.local_name == #key_attribute)
+          .cloned(),
+        _ => None,
       }
+      .ok_or_else(|| yaserde::Error::MissingAttribute(#key_attribute.to_string()))?;
+      let yaserde_entry = (reader.deserialize_attribute(&yaserde_key)?, #deserialize(reader)?);
+      ::std::iter::FromIterator::from_iter(Some(yaserde_entry))
+    }},
+    None => quote!(#deserialize(reader)?),
+  }
+}
+
+/// Value of a field never read from the input.
+pub fn build_default_value(field_attrs: &YaSerdeAttribute) -> TokenStream {
+  match field_attrs.default {
+    Some(ref d) => {
+      let default_function = Ident::new(d, Span::call_site());
+      quote!(#default_function())
     }
-  })
+    None => quote!(Default::default()),
+  }
 }
 
-fn build_call_visitor(
-  field_type: &TokenStream,
-  visitor: &Ident,
-  action: &TokenStream,
-  visitor_label: &Ident,
-  label: &Option<Ident>,
+/// Name of the child elements read for a field: its `wrapper`, the
+/// `inner_wrapper` when the items of a sequence are sequences themselves, else
+/// its label.
+pub fn build_element_name(
+  field_type: &FieldType,
+  field_attrs: &YaSerdeAttribute,
   label_name: &str,
-) -> Option<TokenStream> {
-  Some(quote! {
-    #label_name => {
-      let visitor = #visitor_label{};
+) -> String {
+  if let Some(ref wrapper) = field_attrs.wrapper {
+    return wrapper.clone();
+  }
 
-      if let XmlEvent::StartElement { .. } = *reader.peek()? {
-        reader.set_map_value()
-      }
+  let item_type = match *field_type {
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Vec { ref data_type, .. } => Some(data_type),
+      _ => None,
+    },
+    FieldType::Vec { ref data_type, .. } => Some(data_type),
+    _ => None,
+  };
+
+  match (item_type, field_attrs.inner_wrapper.as_ref()) {
+    (Some(item_type), Some(inner_wrapper)) if item_type.is_sequence() => inner_wrapper.clone(),
+    _ => label_name.to_string(),
+  }
+}
 
-      let result = reader.read_inner_value::<#field_type, _>(|reader| {
-        if let XmlEvent::EndElement { .. } = *reader.peek()? {
-          return visitor.#visitor("");
-        }
+/// Store the value read from a child element into the variable of a field. The
+/// items of a `wrapper` field are all read from the wrapper element.
+pub fn build_set_element_value(
+  field_type: &FieldType,
+  field_attrs: &YaSerdeAttribute,
+  variable: &Ident,
+  label_name: &str,
+) -> TokenStream {
+  if field_attrs.list {
+    let arguments = build_list_arguments(field_attrs);
+    return build_set_list_value(
+      field_type,
+      variable,
+      &quote!(reader.deserialize_element_list_with(#arguments)?),
+      label_name,
+    );
+  }
 
-        if let Ok(XmlEvent::Characters(s)) = reader.next_event() {
-          visitor.#visitor(&s)
-        } else {
-          Err(format!("unable to parse content for {}", #label_name))
-        }
-      });
+  let value = build_element_value(field_attrs);
+  let inner_wrapper = field_attrs
+    .inner_wrapper
+    .as_ref()
+    .map_or(label_name, String::as_str);
 
-      if let Ok(value) = result {
-        #label#action
+  if field_attrs.wrapper.is_none() {
+    if field_attrs.item.is_some() {
+      panic!("item of field {:?} needs a wrapper", label_name);
+    }
+    return build_set_value(
+      field_type,
+      variable,
+      &value,
+      label_name,
+      inner_wrapper,
+      true,
+    );
+  }
+
+  let item_name = field_attrs.item.as_ref().map_or(label_name, String::as_str);
+  match *field_type {
+    FieldType::Vec { .. } => {
+      let items = build_item_value(field_type, &value, item_name, inner_wrapper, 0);
+      quote! {
+        #variable = #items;
       }
     }
-  })
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Vec { .. } => {
+        let items = build_item_value(data_type, &value, item_name, inner_wrapper, 0);
+        quote! {
+          #variable = Some(#items);
+        }
+      }
+      _ => unsupported(label_name),
+    },
+    _ => unsupported(label_name),
+  }
+}
+
+/// Separator and deserializer given to the `deserialize_*_list_with` methods of
+/// the reader for a `list` field.
+pub fn build_list_arguments(field_attrs: &YaSerdeAttribute) -> TokenStream {
+  let separator = match field_attrs.separator {
+    Some(ref separator) => quote!(Some(#separator)),
+    None => quote!(None),
+  };
+  let deserialize = deserializer(field_attrs);
+  quote!(#separator, #deserialize)
 }
 
-fn build_call_visitor_for_attribute(
-  label: &Option<Ident>,
+/// Store the `items` of a `list` field, all read from a single value.
+pub fn build_set_list_value(
+  field_type: &FieldType,
+  variable: &Ident,
+  items: &TokenStream,
   label_name: &str,
-  action: &TokenStream,
-  visitor: &TokenStream,
-  visitor_label: &Ident,
-) -> Option<TokenStream> {
-  Some(quote! {
-    for attr in attributes {
-      if attr.name.local_name == #label_name {
-        let visitor = #visitor_label{};
-        match visitor.#visitor(&attr.value) {
-          Ok(value) => {#label #action;}
-          Err(msg) => {return Err(msg);}
+) -> TokenStream {
+  match *field_type {
+    FieldType::Vec { ref data_type, .. } => match **data_type {
+      FieldType::Value => quote! {
+        #variable = #items.into_iter().collect();
+      },
+      _ => unsupported(label_name),
+    },
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Vec { ref data_type, .. } => match **data_type {
+        FieldType::Value => quote! {
+          #variable = Some(#items.into_iter().collect());
+        },
+        _ => unsupported(label_name),
+      },
+      _ => unsupported(label_name),
+    },
+    _ => unsupported(label_name),
+  }
+}
+
+/// Store one deserialized `value` into the variable of a field. Only fields read
+/// from elements can be `repeated`, attributes and text are read once.
+/// Sequences of sequences read their items from `inner_wrapper` elements.
+pub fn build_set_value(
+  field_type: &FieldType,
+  variable: &Ident,
+  value: &TokenStream,
+  label_name: &str,
+  inner_wrapper: &str,
+  repeated: bool,
+) -> TokenStream {
+  match *field_type {
+    FieldType::Value => quote! {
+      #variable = Some(#value);
+    },
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Value => quote! {
+        #variable = Some(#value);
+      },
+      FieldType::Vec {
+        ref data_type,
+        sequence,
+      } if repeated => {
+        let push = sequence.push();
+        let item = build_item_value(data_type, value, label_name, inner_wrapper, 0);
+        quote! {
+          #variable.get_or_insert_with(Default::default).#push(#item);
         }
       }
+      FieldType::Map if repeated => quote! {
+        let yaserde_map = #value;
+        match #variable {
+          Some(ref mut map) => map.extend(yaserde_map),
+          None => #variable = Some(yaserde_map),
+        }
+      },
+      _ => unsupported(label_name),
+    },
+    FieldType::Vec {
+      ref data_type,
+      sequence,
+    } if repeated => {
+      let push = sequence.push();
+      let item = build_item_value(data_type, value, label_name, inner_wrapper, 0);
+      quote! {
+        #variable.#push(#item);
+      }
     }
-  })
+    FieldType::Vec { .. } => unsupported(label_name),
+    FieldType::Map if repeated => quote! {
+      let yaserde_map = ::std::mem::replace(&mut #variable, #value);
+      #variable.extend(yaserde_map);
+    },
+    FieldType::Map => unsupported(label_name),
+  }
 }
 
-fn build_set_text_to_value(
-  field_attrs: &YaSerdeAttribute,
-  label: &Option<Ident>,
-  action: &TokenStream,
-) -> Option<TokenStream> {
-  if field_attrs.text {
-    Some(quote! {
-      #label = #action;
-    })
-  } else {
-    None
+/// Item of a sequence read from the element at the reader position. `value`
+/// reads the innermost items, nil elements give `None`.
+fn build_item_value(
+  field_type: &FieldType,
+  value: &TokenStream,
+  label_name: &str,
+  inner_wrapper: &str,
+  depth: usize,
+) -> TokenStream {
+  match *field_type {
+    FieldType::Value | FieldType::Map => value.clone(),
+    FieldType::Option { ref data_type } => {
+      let item = build_item_value(data_type, value, label_name, inner_wrapper, depth);
+      quote! {
+        if reader.is_nil_element()? {
+          reader.skip_element()?;
+          None
+        } else {
+          Some(#item)
+        }
+      }
+    }
+    FieldType::Vec { ref data_type, .. } => {
+      let item_name = if data_type.is_sequence() {
+        inner_wrapper
+      } else {
+        label_name
+      };
+      let item = build_item_value(data_type, value, label_name, inner_wrapper, depth + 1);
+      let start = Ident::new(&format!("yaserde_start_{}", depth), Span::call_site());
+      let items = Ident::new(&format!("yaserde_items_{}", depth), Span::call_site());
+      quote! {{
+        let #start = match reader.next_event()? {
+          XmlEvent::StartElement { name, .. } => name,
+          event => return Err(yaserde::Error::UnexpectedEvent(format!("{:?}", event))),
+        };
+        let mut #items = Vec::new();
+        loop {
+          match reader.peek()?.to_owned() {
+            XmlEvent::StartElement { ref name, .. } if name.local_name == #item_name => {
+              #items.push(#item);
+            }
+            XmlEvent::StartElement { .. } => reader.skip_element()?,
+            XmlEvent::Characters(_) => {
+              reader.next_event()?;
+            }
+            XmlEvent::EndElement { .. } => break,
+            event => return Err(yaserde::Error::UnexpectedEvent(format!("{:?}", event))),
+          }
+        }
+        reader.expect_end_element(&#start)?;
+        #items.into_iter().collect()
+      }}
+    }
   }
 }
+
+fn unsupported(label_name: &str) -> ! {
+  panic!("unsupported type for field {:?}", label_name)
+}
@@ -0,0 +1,379 @@
+use attribute::*;
+use field_type::*;
+use proc_macro2::{Span, TokenStream};
+use quote::TokenStreamExt;
+use std::collections::BTreeMap;
+use syn::DataStruct;
+use syn::Fields;
+use syn::Generics;
+use syn::Ident;
+
+use de::expand_struct::{
+  build_default_value, build_element_name, build_list_arguments, build_set_element_value,
+  build_set_list_value, build_set_value, deserializer,
+};
+use ser::expand_tuple_struct::is_newtype;
+
+pub fn parse(
+  data_struct: &DataStruct,
+  name: &Ident,
+  generics: &Generics,
+  root: &str,
+  root_attrs: &YaSerdeAttribute,
+) -> TokenStream {
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  if is_newtype(&data_struct.fields) {
+    let deserialize = deserializer(&YaSerdeAttribute::parse(
+      &data_struct.fields.iter().next().unwrap().attrs,
+    ));
+    return quote! {
+      impl #impl_generics YaDeserialize for #name #ty_generics #where_clause {
+        fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, yaserde::Error> {
+          Ok(#name(#deserialize(reader)?))
+        }
+      }
+    };
+  }
+
+  let build_tuple_struct = build_tuple_struct(
+    &data_struct.fields,
+    &quote!(#name),
+    root,
+    &root_attrs.namespaces,
+    root_attrs.deny_unknown_fields,
+    root_attrs.use_default,
+  );
+
+  quote! {
+    use xml::reader::XmlEvent;
+
+    impl #impl_generics YaDeserialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn deserialize<R: Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, yaserde::Error> {
+        Ok(#build_tuple_struct)
+      }
+    }
+  }
+}
+
+/// Expression reading the element at the reader position into `constructor`
+/// with unnamed `fields`, for tuple structs and tuple variants.
+pub fn build_tuple_struct(
+  fields: &Fields,
+  constructor: &TokenStream,
+  root: &str,
+  namespaces: &BTreeMap<String, String>,
+  deny_unknown_fields: bool,
+  use_default: bool,
+) -> TokenStream {
+  let validate_namespace: TokenStream = namespaces
+    .values()
+    .map(|namespace| {
+      quote!(
+        let mut found = false;
+        debug!("{:?}", namespace);
+        for (key, value) in &namespace {
+          if #namespace == value {
+            found = true;
+          }
+        }
+        if !found {
+          return Err(yaserde::Error::Namespace {
+            expected: #namespace.to_string(),
+          });
+        }
+      )
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let variables: TokenStream = fields
+    .iter()
+    .enumerate()
+    .map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      let variable = build_variable_name(index);
+      let field_type = &field.ty;
+      if field_attrs.skip_deserializing {
+        return TokenStream::new();
+      }
+
+      let init = build_default_value(&field_attrs);
+
+      match get_field_type(field) {
+        FieldType::Value => quote! {
+          #[allow(unused_mut)]
+          let mut #variable: Option<#field_type> = None;
+        },
+        _ => quote! {
+          #[allow(unused_mut)]
+          let mut #variable: #field_type = #init;
+        },
+      }
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let attributes_loading: TokenStream = fields
+    .iter()
+    .enumerate()
+    .filter_map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      if field_attrs.skip_deserializing || !field_attrs.attribute {
+        return None;
+      }
+
+      let label_name = field_attrs
+        .rename
+        .clone()
+        .unwrap_or_else(|| panic!("attributes of tuple structs need a rename"));
+      let variable = build_variable_name(index);
+      let field_type = get_field_type(field);
+      let action = if field_attrs.list {
+        let arguments = build_list_arguments(&field_attrs);
+        build_set_list_value(
+          &field_type,
+          &variable,
+          &quote!(reader.deserialize_attribute_list_with(attribute, #arguments)?),
+          &label_name,
+        )
+      } else {
+        let deserialize = deserializer(&field_attrs);
+        build_set_value(
+          &field_type,
+          &variable,
+          &quote!(reader.deserialize_attribute_with(attribute, #deserialize)?),
+          &label_name,
+          &label_name,
+          false,
+        )
+      };
+
+      let aliases = &field_attrs.aliases;
+      Some(quote! {
+        #label_name #(| #aliases)* => {
+          #action
+        }
+      })
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  // Child elements are assigned to the fields in order: an element goes to the
+  // first field not filled yet whose name matches.
+  let call_visitors: TokenStream = fields
+    .iter()
+    .enumerate()
+    .filter_map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse_unnamed(index, field).with_default_wrapper(field);
+      if field_attrs.skip_deserializing || field_attrs.attribute || field_attrs.text {
+        return None;
+      }
+
+      let label_name = field_attrs.rename.clone().unwrap_or_default();
+      let field_type = get_field_type(field);
+      let element_name = build_element_name(&field_type, &field_attrs, &label_name);
+      let variable = build_variable_name(index);
+      let action = build_set_element_value(&field_type, &field_attrs, &variable, &label_name);
+      let next_index = match field_type {
+        _ if field_attrs.wrapper.is_some() || field_attrs.list => index + 1,
+        FieldType::Vec { .. } | FieldType::Map => index,
+        FieldType::Option { ref data_type } => match **data_type {
+          FieldType::Vec { .. } | FieldType::Map => index,
+          _ => index + 1,
+        },
+        FieldType::Value => index + 1,
+      };
+
+      let aliases = &field_attrs.aliases;
+      Some(quote! {
+        if yaserde_index <= #index && (name.local_name == #element_name #(|| name.local_name == #aliases)*) {
+          #action
+          yaserde_index = #next_index;
+          continue;
+        }
+      })
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let set_text: TokenStream = fields
+    .iter()
+    .enumerate()
+    .filter_map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      if field_attrs.skip_deserializing || !field_attrs.text {
+        return None;
+      }
+
+      let variable = build_variable_name(index);
+      let field_type = get_field_type(field);
+      if field_attrs.list {
+        let arguments = build_list_arguments(&field_attrs);
+        return Some(build_set_list_value(
+          &field_type,
+          &variable,
+          &quote!(reader.deserialize_text_list_with(&text_content, #arguments)?),
+          "",
+        ));
+      }
+
+      let deserialize = deserializer(&field_attrs);
+      Some(build_set_value(
+        &field_type,
+        &variable,
+        &quote!(reader.deserialize_text_with(&text_content, #deserialize)?),
+        "",
+        "",
+        false,
+      ))
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let struct_builder: TokenStream = fields
+    .iter()
+    .enumerate()
+    .map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse_unnamed(index, field);
+      let variable = build_variable_name(index);
+
+      let value = match get_field_type(field) {
+        _ if field_attrs.skip_deserializing => build_default_value(&field_attrs),
+        FieldType::Value => {
+          if let Some(ref d) = field_attrs.default {
+            let default_function = Ident::new(d, Span::call_site());
+            quote!(#variable.unwrap_or_else(#default_function))
+          } else if field_attrs.use_default || use_default {
+            quote!(#variable.unwrap_or_default())
+          } else if field_attrs.text {
+            let deserialize = deserializer(&field_attrs);
+            quote! {
+              match #variable {
+                Some(value) => value,
+                None => reader.deserialize_text_with("", #deserialize)?,
+              }
+            }
+          } else {
+            let label_name = field_attrs.rename.clone().unwrap_or_default();
+            let missing = if field_attrs.attribute {
+              quote!(yaserde::Error::MissingAttribute(#label_name.to_string()))
+            } else {
+              quote!(yaserde::Error::MissingElement(#label_name.to_string()))
+            };
+            quote!(#variable.ok_or_else(|| #missing)?)
+          }
+        }
+        _ => quote!(#variable),
+      };
+
+      quote! {
+        #value,
+      }
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let unknown_attribute = if deny_unknown_fields {
+    quote!(return Err(yaserde::Error::UnknownAttribute(attribute.name.local_name.clone()));)
+  } else {
+    TokenStream::new()
+  };
+
+  let load_attributes = if attributes_loading.is_empty() && !deny_unknown_fields {
+    None
+  } else {
+    Some(quote! {
+      for attribute in &attributes {
+        match attribute.name.local_name.as_str() {
+          #attributes_loading
+          _ => {
+            #unknown_attribute
+          }
+        }
+      }
+    })
+  };
+
+  let skip_unknown = if deny_unknown_fields {
+    quote!(return Err(yaserde::Error::UnknownElement(name.local_name.clone()));)
+  } else {
+    quote!(reader.skip_element()?;)
+  };
+
+  let read_text = if set_text.is_empty() {
+    quote! {
+      XmlEvent::Characters(_) => {
+        reader.next_event()?;
+      }
+    }
+  } else {
+    quote! {
+      XmlEvent::Characters(text_content) => {
+        reader.next_event()?;
+        #set_text
+      }
+    }
+  };
+
+  quote! {{
+    let (named_element, attributes) =
+      match reader.peek()?.to_owned() {
+        XmlEvent::StartElement{name, attributes, namespace} => {
+          #validate_namespace
+          (name, attributes)
+        }
+        event => {
+          return Err(yaserde::Error::UnexpectedEvent(format!("{:?}, expected <{}>", event, #root)))
+        }
+      };
+    reader.next_event()?;
+    let depth = reader.depth();
+    debug!("Tuple struct: start to parse {:?}", named_element.local_name);
+
+    #variables
+    #load_attributes
+
+    #[allow(unused_mut)]
+    let mut yaserde_index = 0;
+    loop {
+      match reader.peek()?.to_owned() {
+        XmlEvent::StartElement{ref name, ..} => {
+          #call_visitors
+          #skip_unknown
+        }
+        XmlEvent::EndElement{..} => {
+          if reader.depth() == depth {
+            break;
+          }
+          reader.next_event()?;
+        }
+        #read_text
+        event => {
+          return Err(yaserde::Error::UnexpectedEvent(format!("{:?}", event)))
+        }
+      }
+    }
+    let value = #constructor(#struct_builder);
+    reader.expect_end_element(&named_element)?;
+    value
+  }}
+}
+
+/// Local variable accumulating the value of the field at `index`.
+pub fn build_variable_name(index: usize) -> Ident {
+  Ident::new(&format!("__{}", index), Span::call_site())
+}
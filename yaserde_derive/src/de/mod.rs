@@ -1,39 +1,58 @@
-
-pub mod build_default_value;
 pub mod expand_enum;
+pub mod expand_from;
 pub mod expand_struct;
+pub mod expand_tuple_struct;
 
 use attribute;
-use proc_macro2::{Span, TokenStream};
+use bound;
+use case;
+use proc_macro2::TokenStream;
 use syn;
-use syn::Ident;
 
 pub fn expand_derive_deserialize(ast: &syn::DeriveInput) -> Result<TokenStream, String> {
+  let root_attrs = attribute::YaSerdeAttribute::parse(&ast.attrs);
+  let ast = case::apply_rename_all(ast, &root_attrs.rename_all)?;
   let name = &ast.ident;
-  let attrs = &ast.attrs;
   let data = &ast.data;
 
-  let root_attrs = attribute::YaSerdeAttribute::parse(attrs);
   let root = root_attrs.clone().root.unwrap_or_else(|| name.to_string());
 
-  let impl_block = match *data {
-    syn::Data::Struct(ref data_struct) => {
-      expand_struct::parse(data_struct, name, &root, &root_attrs.namespaces)
-    }
-    syn::Data::Enum(ref data_enum) => {
-      expand_enum::parse(data_enum, name, &root, &root_attrs.namespaces)
-    }
-    syn::Data::Union(ref _data_union) => unimplemented!(),
+  let types = match root_attrs.from.as_ref().or(root_attrs.try_from.as_ref()) {
+    Some(from) => vec![attribute::parse_type("from", from)],
+    None => bound::field_types(data, |field_attrs| {
+      !field_attrs.skip_deserializing && field_attrs.deserialize_with.is_none()
+    }),
   };
-
-  let dummy_const = Ident::new(
-    &format!("_IMPL_YA_DESERIALIZE_FOR_{}", name),
-    Span::call_site(),
+  let generics = bound::with_bound(
+    &ast.generics,
+    &types,
+    &root_attrs.deserialize_bound,
+    &quote!(YaDeserialize),
   );
 
+  let impl_block = if let Some(ref from) = root_attrs.from {
+    expand_from::parse(name, &generics, from, false)
+  } else if let Some(ref try_from) = root_attrs.try_from {
+    expand_from::parse(name, &generics, try_from, true)
+  } else {
+    let generics = bound::with_default_bound(&generics, data, &root_attrs);
+    match *data {
+      syn::Data::Struct(ref data_struct) => match data_struct.fields {
+        syn::Fields::Unnamed(_) => {
+          expand_tuple_struct::parse(data_struct, name, &generics, &root, &root_attrs)
+        }
+        _ => expand_struct::parse(data_struct, name, &generics, &root, &root_attrs),
+      },
+      syn::Data::Enum(ref data_enum) => {
+        expand_enum::parse(data_enum, name, &generics, &root, &root_attrs)
+      }
+      syn::Data::Union(ref _data_union) => unimplemented!(),
+    }
+  };
+
   let generated = quote! {
     #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
-    const #dummy_const: () = {
+    const _: () = {
       extern crate yaserde as _yaserde;
       #impl_block
     };
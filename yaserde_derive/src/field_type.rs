@@ -1,78 +1,99 @@
+use proc_macro2::TokenStream;
 use syn;
 use syn::punctuated::Pair;
-use syn::Type::Path;
 
+/// Shape of a field type.
+///
+/// Only the wrappers changing how many elements map to the field are told apart,
+/// every other type is handled through its `YaSerialize`/`YaDeserialize` impls.
 #[derive(Debug)]
 pub enum FieldType {
-  FieldTypeString,
-  FieldTypeBool,
-  FieldTypeI8,
-  FieldTypeU8,
-  FieldTypeI16,
-  FieldTypeU16,
-  FieldTypeI32,
-  FieldTypeU32,
-  FieldTypeI64,
-  FieldTypeU64,
-  FieldTypeF32,
-  FieldTypeF64,
-  FieldTypeOption { data_type: Box<FieldType> },
-  FieldTypeVec { data_type: Box<FieldType> },
-  FieldTypeStruct { struct_name: syn::Ident },
+  /// `Option<T>`: the element may be absent.
+  Option { data_type: Box<FieldType> },
+  /// `Vec<T>` or another sequence: the element is repeated.
+  Vec {
+    data_type: Box<FieldType>,
+    sequence: Sequence,
+  },
+  /// `HashMap<K, V>` or `BTreeMap<K, V>`: entries of repeated elements are merged.
+  Map,
+  /// Any other type.
+  Value,
 }
 
 impl FieldType {
-  fn from_ident(t: &syn::PathSegment) -> Option<FieldType> {
-    match t.ident.to_string().as_str() {
-      "String" => Some(FieldType::FieldTypeString),
-      "bool" => Some(FieldType::FieldTypeBool),
-      "i8" => Some(FieldType::FieldTypeI8),
-      "u8" => Some(FieldType::FieldTypeU8),
-      "i16" => Some(FieldType::FieldTypeI16),
-      "u16" => Some(FieldType::FieldTypeU16),
-      "i32" => Some(FieldType::FieldTypeI32),
-      "u32" => Some(FieldType::FieldTypeU32),
-      "i64" => Some(FieldType::FieldTypeI64),
-      "u64" => Some(FieldType::FieldTypeU64),
-      "f32" => Some(FieldType::FieldTypeF32),
-      "f64" => Some(FieldType::FieldTypeF64),
-      "Option" => get_sub_type(t).map(|data_type| FieldType::FieldTypeOption {
-        data_type: Box::new(FieldType::from_ident(&data_type).unwrap()),
-      }),
-      "Vec" => get_sub_type(t).map(|data_type| FieldType::FieldTypeVec {
-        data_type: Box::new(FieldType::from_ident(&data_type).unwrap()),
-      }),
-      _struct_name => Some(FieldType::FieldTypeStruct {
-        struct_name: t.ident.clone(),
-      }),
+  /// Whether an item of this type in a sequence is a sequence itself, written
+  /// as a wrapper element holding its own items.
+  pub fn is_sequence(&self) -> bool {
+    match *self {
+      FieldType::Vec { .. } => true,
+      FieldType::Option { ref data_type } => data_type.is_sequence(),
+      _ => false,
     }
   }
 }
 
-pub fn get_field_type(field: &syn::Field) -> Option<FieldType> {
-  match field.ty {
-    Path(ref path) => {
-      if path.path.segments.len() != 1 {
-        return None;
-      }
-      match path.path.segments.first() {
-        Some(Pair::End(t)) => FieldType::from_ident(t),
-        _ => None,
-      }
+/// Collection holding the items of a repeated element.
+#[derive(Debug, Clone, Copy)]
+pub enum Sequence {
+  Vec,
+  VecDeque,
+  /// `HashSet<T>` or `BTreeSet<T>`.
+  Set,
+}
+
+impl Sequence {
+  /// Method adding an item to the collection.
+  pub fn push(self) -> TokenStream {
+    match self {
+      Sequence::Vec => quote!(push),
+      Sequence::VecDeque => quote!(push_back),
+      Sequence::Set => quote!(insert),
     }
-    _ => None,
   }
 }
 
-fn get_sub_type(t: &syn::PathSegment) -> Option<syn::PathSegment> {
-  if let syn::PathArguments::AngleBracketed(ref args) = t.arguments {
-    if let Some(Pair::End(tt)) = args.args.first() {
-      if let syn::GenericArgument::Type(ref argument) = *tt {
-        if let Path(ref path2) = *argument {
-          if let Some(Pair::End(ttt)) = path2.path.segments.first() {
-            return Some(ttt.clone());
+pub fn get_field_type(field: &syn::Field) -> FieldType {
+  get_type(&field.ty)
+}
+
+fn get_type(ty: &syn::Type) -> FieldType {
+  match *ty {
+    // types coming from a `$t:ty` macro fragment are wrapped in an invisible group
+    syn::Type::Group(ref group) => get_type(&group.elem),
+    syn::Type::Path(ref path) if path.qself.is_none() => {
+      let wrapper = path.path.segments.last().map(Pair::into_value);
+      match wrapper.map(|segment| (segment, get_sub_type(segment))) {
+        Some((segment, _)) if segment.ident == "HashMap" || segment.ident == "BTreeMap" => {
+          FieldType::Map
+        }
+        Some((segment, Some(sub_type))) if segment.ident == "Option" => FieldType::Option {
+          data_type: Box::new(get_type(sub_type)),
+        },
+        Some((segment, Some(sub_type))) => {
+          let sequence = match segment.ident.to_string().as_str() {
+            "Vec" => Sequence::Vec,
+            "VecDeque" => Sequence::VecDeque,
+            "HashSet" | "BTreeSet" => Sequence::Set,
+            _ => return FieldType::Value,
+          };
+          FieldType::Vec {
+            data_type: Box::new(get_type(sub_type)),
+            sequence,
           }
         }
+        _ => FieldType::Value,
+      }
+    }
+    _ => FieldType::Value,
+  }
+}
+
+fn get_sub_type(t: &syn::PathSegment) -> Option<&syn::Type> {
+  if let syn::PathArguments::AngleBracketed(ref args) = t.arguments {
+    if args.args.len() == 1 {
+      if let Some(Pair::End(syn::GenericArgument::Type(ref argument))) = args.args.first() {
+        return Some(argument);
       }
     }
   }
@@ -4,9 +4,12 @@ extern crate proc_macro;
 extern crate proc_macro2;
 #[macro_use]
 extern crate quote;
+#[macro_use]
 extern crate syn;
 
 mod attribute;
+mod bound;
+mod case;
 mod de;
 mod field_type;
 mod ser;
@@ -18,7 +21,7 @@ pub fn derive_deserialize(input: TokenStream) -> TokenStream {
   let ast = syn::parse(input).unwrap();
   match de::expand_derive_deserialize(&ast) {
     Ok(expanded) => expanded.into(),
-    Err(msg) => panic!(msg),
+    Err(msg) => panic!("{}", msg),
   }
 }
 
@@ -27,6 +30,6 @@ pub fn derive_serialize(input: TokenStream) -> TokenStream {
   let ast = syn::parse(input).unwrap();
   match ser::expand_derive_serialize(&ast) {
     Ok(expanded) => expanded.into(),
-    Err(msg) => panic!(msg),
+    Err(msg) => panic!("{}", msg),
   }
 }
@@ -1,75 +1,420 @@
-
+use attribute::{parse_function_path, YaSerdeAttribute};
+use field_type::*;
 use proc_macro2::{Ident, Span, TokenStream};
+use quote::TokenStreamExt;
 
+/// Name of the element or attribute written for a field, with its prefix.
+/// Tuple struct attributes have no name unless they are renamed.
+pub fn build_label_name(field: &syn::Field, field_attrs: &YaSerdeAttribute) -> Option<String> {
+  let renamed_label = match field_attrs.rename {
+    Some(ref value) => value.to_string(),
+    None => field.ident.as_ref()?.to_string(),
+  };
 
-pub fn enclose_formatted_characters(
-  label: &Ident,
-  label_name: String
-  ) -> TokenStream {
-  quote! {
-    let start_event = XmlEvent::start_element(#label_name);
-    let _ret = writer.write(start_event);
+  match field_attrs.prefix {
+    Some(ref prefix) => Some(format!("{}:{}", prefix, renamed_label)),
+    None => Some(renamed_label),
+  }
+}
 
-    let yas_value = format!("{}", &self.#label);
-    let data_event = XmlEvent::characters(&yas_value);
-    let _ret = writer.write(data_event);
+/// Function writing the items of a field, its `serialize_with` function or
+/// `YaSerialize::serialize`.
+pub fn serializer(field_attrs: &YaSerdeAttribute) -> TokenStream {
+  match field_attrs.serialize_with {
+    Some(ref path) => {
+      let path = parse_function_path("serialize_with", path);
+      quote!(#path)
+    }
+    None => quote!(yaserde::YaSerialize::serialize),
+  }
+}
+
+/// Skip `inner` when the field equals the value of its `default` function, or
+/// when its `skip_serializing_if` predicate holds.
+fn unless_default(
+  value: &TokenStream,
+  field_attrs: &YaSerdeAttribute,
+  inner: TokenStream,
+) -> TokenStream {
+  let inner = match field_attrs.default {
+    Some(ref d) => {
+      let default_function = Ident::new(d, Span::call_site());
+      quote! {
+        if *#value != #default_function() {
+          #inner
+        }
+      }
+    }
+    None => inner,
+  };
 
-    let end_event = XmlEvent::end_element();
-    let _ret = writer.write(end_event);
+  match field_attrs.skip_serializing_if {
+    Some(ref predicate) => {
+      let predicate = parse_function_path("skip_serializing_if", predicate);
+      quote! {
+        if !#predicate(#value) {
+          #inner
+        }
+      }
+    }
+    None => inner,
   }
 }
 
-pub fn enclose_formatted_characters_for_value(
-  label: &Ident,
-  label_name: String
-  ) -> TokenStream {
-  quote! {
-    let start_event = XmlEvent::start_element(#label_name);
-    let _ret = writer.write(start_event);
+/// Add a field to the attributes of the start element. `value` is a reference
+/// to the field.
+pub fn serialize_attribute(
+  field: &syn::Field,
+  field_attrs: &YaSerdeAttribute,
+  value: &TokenStream,
+) -> TokenStream {
+  if field_attrs.flatten {
+    return serialize_flattened_attributes(field, field_attrs, value);
+  }
 
-    let value = format!("{}", #label);
-    let data_event = XmlEvent::characters(&value);
-    let _ret = writer.write(data_event);
+  let label_name = build_label_name(field, field_attrs)
+    .unwrap_or_else(|| panic!("attributes of tuple structs need a rename"));
 
-    let end_event = XmlEvent::end_element();
-    let _ret = writer.write(end_event);
+  if field_attrs.list {
+    let inner = serialize_list(
+      field,
+      field_attrs,
+      &label_name,
+      value,
+      |text| quote!(writer.add_attribute(#label_name, #text);),
+    );
+    return unless_default(value, field_attrs, inner);
   }
+
+  let serialize = serializer(field_attrs);
+  let add_attribute = |value: TokenStream| {
+    quote! {
+      let yaserde_value = writer.capture_text(|writer| #serialize(#value, writer))?;
+      writer.add_attribute(#label_name, yaserde_value);
+    }
+  };
+
+  let inner = match get_field_type(field) {
+    FieldType::Value => add_attribute(value.clone()),
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Value => {
+        let add_attribute = add_attribute(quote!(yaserde_item));
+        quote! {
+          if let Some(ref yaserde_item) = *#value {
+            #add_attribute
+          }
+        }
+      }
+      _ => unsupported(&label_name),
+    },
+    FieldType::Vec { .. } | FieldType::Map => unsupported(&label_name),
+  };
+
+  unless_default(value, field_attrs, inner)
 }
 
-pub fn enclose_characters(
-  label: &Option<Ident>,
-  label_name: String
-  ) -> TokenStream {
-  quote! {
-    let start_event = XmlEvent::start_element(#label_name);
-    let _ret = writer.write(start_event);
+/// Add the attributes of a flattened field to the start element.
+fn serialize_flattened_attributes(
+  field: &syn::Field,
+  field_attrs: &YaSerdeAttribute,
+  value: &TokenStream,
+) -> TokenStream {
+  let add_attributes = |value: TokenStream| {
+    quote! {
+      yaserde::YaSerialize::serialize_attributes(#value, writer)?;
+    }
+  };
+
+  let label_name = build_label_name(field, field_attrs).unwrap_or_default();
+  let inner = match get_field_type(field) {
+    FieldType::Value => add_attributes(value.clone()),
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Value => {
+        let add_attributes = add_attributes(quote!(yaserde_item));
+        quote! {
+          if let Some(ref yaserde_item) = *#value {
+            #add_attributes
+          }
+        }
+      }
+      _ => unsupported(&label_name),
+    },
+    FieldType::Vec { ref data_type, .. } => match **data_type {
+      FieldType::Value => {
+        let add_attributes = add_attributes(quote!(yaserde_item));
+        quote! {
+          for yaserde_item in #value {
+            #add_attributes
+          }
+        }
+      }
+      _ => unsupported(&label_name),
+    },
+    FieldType::Map => unsupported(&label_name),
+  };
+
+  unless_default(value, field_attrs, inner)
+}
 
-    let value = format!("{}", self.#label);
-    let data_event = XmlEvent::characters(&value);
-    let _ret = writer.write(data_event);
+/// Join the items of a `list` field into a single text passed to `write_text`.
+fn serialize_list<F>(
+  field: &syn::Field,
+  field_attrs: &YaSerdeAttribute,
+  label_name: &str,
+  value: &TokenStream,
+  write_text: F,
+) -> TokenStream
+where
+  F: Fn(TokenStream) -> TokenStream,
+{
+  let serialize = serializer(field_attrs);
+  let separator = field_attrs.separator.as_deref().unwrap_or(" ");
+  let write_text = write_text(quote!(yaserde_value));
+  let write_list = |items: TokenStream| {
+    quote! {
+      let yaserde_value = writer.capture_list_text(#items, #separator, #serialize)?;
+      #write_text
+    }
+  };
 
-    let end_event = XmlEvent::end_element();
-    let _ret = writer.write(end_event);
+  match get_field_type(field) {
+    FieldType::Vec { ref data_type, .. } => match **data_type {
+      FieldType::Value => write_list(value.clone()),
+      _ => unsupported(label_name),
+    },
+    FieldType::Option { ref data_type } => match **data_type {
+      FieldType::Vec { ref data_type, .. } => match **data_type {
+        FieldType::Value => {
+          let write_list = write_list(quote!(yaserde_item));
+          quote! {
+            if let Some(ref yaserde_item) = *#value {
+              #write_list
+            }
+          }
+        }
+        _ => unsupported(label_name),
+      },
+      _ => unsupported(label_name),
+    },
+    _ => unsupported(label_name),
   }
 }
 
+/// Write a field as child elements, as text content for `text` fields, or as
+/// the content of the enclosing element for `flatten` fields.
+/// `value` is a reference to the field.
 pub fn serialize_element(
-  label: &Option<Ident>,
-  label_name: String,
-  default: &Option<String>,
-  ) -> Option<TokenStream> {
-  let inner = enclose_characters(label, label_name);
-
-  if let Some(ref d) = default {
-    let default_function = Ident::new(&d, Span::call_site());
-    Some(quote! {
-      if self.#label != #default_function() {
-        #inner
+  field: &syn::Field,
+  field_attrs: &YaSerdeAttribute,
+  value: &TokenStream,
+) -> TokenStream {
+  let label_name = match (field_attrs.wrapper.as_ref(), field_attrs.item.as_ref()) {
+    (Some(_), Some(item)) => item.clone(),
+    (None, Some(_)) => panic!(
+      "item of field {:?} needs a wrapper",
+      build_label_name(field, field_attrs).unwrap_or_default()
+    ),
+    _ => build_label_name(field, field_attrs).unwrap_or_default(),
+  };
+
+  if field_attrs.list {
+    let start_event_name = if field_attrs.text {
+      quote!(writer.set_skip_start_end(true);)
+    } else {
+      quote!(writer.set_start_event_name(Some(#label_name.to_string()));)
+    };
+    let inner = serialize_list(field, field_attrs, &label_name, value, |text| {
+      quote! {
+        #start_event_name
+        writer.write_text_value(&#text)?;
+      }
+    });
+    return unless_default(value, field_attrs, inner);
+  }
+
+  let serialize = serializer(field_attrs);
+  let write_item = |value: TokenStream| {
+    if field_attrs.text || field_attrs.flatten {
+      quote! {
+        writer.set_skip_start_end(true);
+        #serialize(#value, writer)?;
+      }
+    } else {
+      quote! {
+        writer.set_start_event_name(Some(#label_name.to_string()));
+        #serialize(#value, writer)?;
+      }
+    }
+  };
+
+  // entries of a map with a `key_attribute` are written as repeated elements
+  let write_map = |value: TokenStream| match field_attrs.key_attribute {
+    Some(ref key_attribute) => {
+      let write_item = write_item(quote!(yaserde_item));
+      quote! {
+        for (yaserde_key, yaserde_item) in #value {
+          let yaserde_key = writer.capture_text(|writer| yaserde::YaSerialize::serialize(yaserde_key, writer))?;
+          writer.add_attribute(#key_attribute, yaserde_key);
+          #write_item
+        }
       }
+    }
+    None => write_item(value),
+  };
+
+  let elements = Elements {
+    write_item: &write_item,
+    write_map: &write_map,
+    label_name: &label_name,
+    inner_wrapper: field_attrs.inner_wrapper.as_ref().unwrap_or(&label_name),
+    wrapper: field_attrs.wrapper.as_deref(),
+    text: field_attrs.text,
+  };
+  let inner = elements.field(&get_field_type(field), value, 0);
+
+  unless_default(value, field_attrs, inner)
+}
+
+/// Elements written for the value of a field depending on its type.
+struct Elements<'a> {
+  write_item: &'a dyn Fn(TokenStream) -> TokenStream,
+  write_map: &'a dyn Fn(TokenStream) -> TokenStream,
+  label_name: &'a str,
+  inner_wrapper: &'a str,
+  wrapper: Option<&'a str>,
+  text: bool,
+}
+
+impl<'a> Elements<'a> {
+  /// Write the field, or the content of an `Option` field. `value` is a reference.
+  fn field(&self, field_type: &FieldType, value: &TokenStream, depth: usize) -> TokenStream {
+    let item = build_item_name(depth);
+    match *field_type {
+      FieldType::Value => (self.write_item)(value.clone()),
+      FieldType::Map if !self.text => (self.write_map)(value.clone()),
+      FieldType::Option { ref data_type } => {
+        let write_field = self.field(data_type, &quote!(#item), depth + 1);
+        quote! {
+          if let Some(ref #item) = *#value {
+            #write_field
+          }
+        }
+      }
+      FieldType::Vec { ref data_type, .. } if !self.text => {
+        let write_item = self.item(data_type, &quote!(#item), depth + 1);
+        let write_items = quote! {
+          for #item in #value {
+            #write_item
+          }
+        };
+
+        match self.wrapper {
+          Some(wrapper) => quote! {
+            writer.write_start_element(XmlEvent::start_element(#wrapper))?;
+            #write_items
+            writer.write(XmlEvent::end_element())?;
+          },
+          None => write_items,
+        }
+      }
+      _ => unsupported(self.label_name),
+    }
+  }
+
+  /// Write an item of a sequence: a nested sequence is wrapped in an
+  /// `inner_wrapper` element and `None` is written as a nil element.
+  fn item(&self, field_type: &FieldType, value: &TokenStream, depth: usize) -> TokenStream {
+    let item = build_item_name(depth);
+    let name = if field_type.is_sequence() {
+      self.inner_wrapper
+    } else {
+      self.label_name
+    };
+
+    match *field_type {
+      FieldType::Value | FieldType::Map => (self.write_item)(value.clone()),
+      FieldType::Option { ref data_type } => {
+        let write_item = self.item(data_type, &quote!(#item), depth + 1);
+        quote! {
+          match *#value {
+            Some(ref #item) => {
+              #write_item
+            }
+            None => {
+              writer.set_start_event_name(Some(#name.to_string()));
+              writer.write_nil_element()?;
+            }
+          }
+        }
+      }
+      FieldType::Vec { ref data_type, .. } => {
+        let write_item = self.item(data_type, &quote!(#item), depth + 1);
+        quote! {
+          writer.write_start_element(XmlEvent::start_element(#name))?;
+          for #item in #value {
+            #write_item
+          }
+          writer.write(XmlEvent::end_element())?;
+        }
+      }
+    }
+  }
+}
+
+fn build_item_name(depth: usize) -> Ident {
+  Ident::new(&format!("yaserde_item_{}", depth), Span::call_site())
+}
+
+/// Attributes of the start element written for `fields`. `value` gives a
+/// reference to the field at an index.
+pub fn serialize_attributes<F>(fields: &syn::Fields, value: F) -> TokenStream
+where
+  F: Fn(usize, &syn::Field) -> TokenStream,
+{
+  fields
+    .iter()
+    .enumerate()
+    .filter_map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      if field_attrs.skip_serializing || !field_attrs.attribute && !field_attrs.flatten {
+        return None;
+      }
+
+      Some(serialize_attribute(
+        field,
+        &field_attrs,
+        &value(index, field),
+      ))
     })
-  } else {
-    Some(quote! {
-      #inner
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
     })
-  }
+}
+
+/// Content written for `fields`. `value` gives a reference to the field at an
+/// index.
+pub fn serialize_elements<F>(fields: &syn::Fields, value: F) -> TokenStream
+where
+  F: Fn(usize, &syn::Field) -> TokenStream,
+{
+  fields
+    .iter()
+    .enumerate()
+    .filter_map(|(index, field)| {
+      let field_attrs = YaSerdeAttribute::parse_unnamed(index, field).with_default_wrapper(field);
+      if field_attrs.skip_serializing || field_attrs.attribute {
+        return None;
+      }
+
+      Some(serialize_element(field, &field_attrs, &value(index, field)))
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    })
+}
+
+fn unsupported(label_name: &str) -> ! {
+  panic!("unsupported type for field {:?}", label_name)
 }
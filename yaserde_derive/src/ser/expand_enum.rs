@@ -1,184 +1,227 @@
 use attribute::*;
-use field_type::*;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::TokenStreamExt;
-use std::collections::BTreeMap;
 use syn::DataEnum;
 use syn::Fields;
+use syn::Generics;
 use syn::Ident;
 
+use de::{expand_struct, expand_tuple_struct};
+use ser::element::*;
+use ser::expand_tuple_struct::is_newtype;
+
 pub fn serialize(
   data_enum: &DataEnum,
   name: &Ident,
+  generics: &Generics,
   root: &str,
-  namespaces: &BTreeMap<String, String>,
+  root_attrs: &YaSerdeAttribute,
 ) -> TokenStream {
+  // struct variants are written inline unless `variant_element` is set
+  let inline = |variant: &syn::Variant| match variant.fields {
+    Fields::Named(_) => !root_attrs.variant_element,
+    _ => false,
+  };
+
   let write_enum_content: TokenStream = data_enum
     .variants
     .iter()
     .map(|variant| {
       let variant_attrs = YaSerdeAttribute::parse(&variant.attrs);
-      let renamed_label = match variant_attrs.rename {
-        Some(value) => Ident::new(&value, Span::call_site()),
-        None => variant.ident.clone(),
-      };
+      let renamed_label = variant_attrs
+        .rename
+        .unwrap_or_else(|| variant.ident.to_string());
       let label = &variant.ident;
       let label_name = if let Some(prefix) = variant_attrs.prefix {
-        prefix + ":" + renamed_label.to_string().as_ref()
+        prefix + ":" + renamed_label.as_ref()
       } else {
-        renamed_label.to_string()
+        renamed_label
       };
 
       match variant.fields {
-        Fields::Unit => Some(quote! {
-          &#name::#label => {
+        Fields::Unit => quote! {
+          #name::#label => {
             let data_event = XmlEvent::characters(#label_name);
-            let _ret = writer.write(data_event);
+            writer.write(data_event)?;
           }
-        }),
-        Fields::Named(ref fields) => {
-          let enum_fields = fields
-            .named
+        },
+        Fields::Unnamed(ref fields) if variant_attrs.text && is_newtype(&variant.fields) => {
+          let serialize = serializer(&YaSerdeAttribute::parse(&fields.unnamed[0].attrs));
+          quote! {
+            #name::#label(ref __0) => {
+              writer.set_skip_start_end(true);
+              #serialize(__0, writer)?;
+            }
+          }
+        }
+        Fields::Unnamed(ref fields) if is_newtype(&variant.fields) => {
+          let serialize = serializer(&YaSerdeAttribute::parse(&fields.unnamed[0].attrs));
+          quote! {
+            #name::#label(ref __0) => {
+              writer.set_start_event_name(Some(#label_name.to_string()));
+              #serialize(__0, writer)?;
+            }
+          }
+        }
+        Fields::Named(_) | Fields::Unnamed(_) => {
+          let variable = |index: usize, field: &syn::Field| match field.ident {
+            Some(_) => expand_struct::build_variable_name(&field.ident),
+            None => expand_tuple_struct::build_variable_name(index),
+          };
+
+          let bindings: TokenStream = variant
+            .fields
             .iter()
-            .map(|field| {
-              let field_attrs = YaSerdeAttribute::parse(&field.attrs);
-              if field_attrs.attribute {
-                return None;
-              }
-
-              let field_label = &field.ident;
-              if field_attrs.text {
-                return Some(quote!(
-                  let data_event = XmlEvent::characters(&self.#field_label);
-                  let _ret = writer.write(data_event);
-                ));
-              }
-
-              let renamed_field_label = match field_attrs.rename {
-                Some(value) => Some(Ident::new(&value.replace("\"", ""), Span::call_site())),
-                None => field.ident.clone(),
-              };
-              let field_label_name = renamed_field_label.unwrap().to_string();
-
-              match get_field_type(field) {
-                Some(FieldType::FieldTypeString) => Some(quote! {
-                  match self {
-                    &#name::#label{ref #field_label, ..} => {
-                      let struct_start_event = XmlEvent::start_element(#field_label_name);
-                      let _ret = writer.write(struct_start_event);
-
-                      let data_event = XmlEvent::characters(#field_label);
-                      let _ret = writer.write(data_event);
-
-                      let struct_end_event = XmlEvent::end_element();
-                      let _ret = writer.write(struct_end_event);
-                    },
-                    _ => {},
-                  }
-                }),
-                Some(FieldType::FieldTypeStruct { .. }) => Some(quote! {
-                  let struct_start_event = XmlEvent::start_element(#field_label_name);
-                  let _ret = writer.write(struct_start_event);
-
-                  match self {
-                    &#name::#label{ref #field_label, ..} => {
-                      writer.set_skip_start_end(true);
-                      if let Err(msg) = #field_label.serialize(writer) {
-                        return Err(msg);
-                      };
-                    },
-                    _ => {}
-                  }
-
-                  let struct_end_event = XmlEvent::end_element();
-                  let _ret = writer.write(struct_end_event);
-                }),
-                Some(FieldType::FieldTypeVec { .. }) => Some(quote! {
-                  match self {
-                    &#name::#label{ref #field_label, ..} => {
-                      for item in #field_label {
-                        let struct_start_event = XmlEvent::start_element(#field_label_name);
-                        let _ret = writer.write(struct_start_event);
-
-                        writer.set_skip_start_end(true);
-                        if let Err(msg) = item.serialize(writer) {
-                          return Err(msg);
-                        };
-                        let struct_end_event = XmlEvent::end_element();
-                        let _ret = writer.write(struct_end_event);
-                      }
-                    },
-                    _ => {}
-                  }
-                }),
-                _ => None,
+            .enumerate()
+            .map(|(index, field)| {
+              let variable = variable(index, field);
+              match field.ident {
+                Some(ref field_label) => quote!(#field_label: ref #variable,),
+                None => quote!(ref #variable,),
               }
             })
-            .filter(|x| x.is_some())
-            .map(|x| x.unwrap())
             .fold(TokenStream::new(), |mut tokens, token| {
               tokens.append_all(token);
               tokens
             });
+          let pattern = match variant.fields {
+            Fields::Named(_) => quote!(#name::#label{#bindings}),
+            _ => quote!(#name::#label(#bindings)),
+          };
+
+          let build_attributes = serialize_attributes(&variant.fields, |index, field| {
+            let variable = variable(index, field);
+            quote!(#variable)
+          });
+          let enum_fields = serialize_elements(&variant.fields, |index, field| {
+            let variable = variable(index, field);
+            quote!(#variable)
+          });
+
+          if inline(variant) {
+            // fields written inline have their attributes on the element of the enum
+            quote! {
+              #pattern => {
+                #enum_fields
+              }
+            }
+          } else {
+            quote! {
+              #pattern => {
+                let struct_start_event = XmlEvent::start_element(#label_name);
+                #build_attributes
+                writer.write_start_element(struct_start_event)?;
+
+                #enum_fields
 
-          Some(quote! {
-            &#name::#label{..} => {
-              #enum_fields
+                let struct_end_event = XmlEvent::end_element();
+                writer.write(struct_end_event)?;
+              }
             }
-          })
+          }
         }
-        Fields::Unnamed(ref _fields) => unimplemented!(),
       }
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
     .fold(TokenStream::new(), |mut tokens, token| {
       tokens.append_all(token);
       tokens
     });
 
-  let add_namespaces: TokenStream = namespaces
+  let write_enum_attributes: TokenStream = data_enum
+    .variants
+    .iter()
+    .filter_map(|variant| {
+      let label = &variant.ident;
+      let fields = match variant.fields {
+        Fields::Named(ref fields) if inline(variant) => fields,
+        _ => return None,
+      };
+
+      let bindings: TokenStream = fields
+        .named
+        .iter()
+        .map(|field| {
+          let field_label = &field.ident;
+          let variable = expand_struct::build_variable_name(field_label);
+          quote!(#field_label: ref #variable,)
+        })
+        .fold(TokenStream::new(), |mut tokens, token| {
+          tokens.append_all(token);
+          tokens
+        });
+      let build_attributes = serialize_attributes(&variant.fields, |_, field| {
+        let variable = expand_struct::build_variable_name(&field.ident);
+        quote!(#variable)
+      });
+
+      Some(quote! {
+        #name::#label{#bindings} => {
+          #build_attributes
+        }
+      })
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let other_variants = if data_enum.variants.iter().all(inline) {
+    TokenStream::new()
+  } else {
+    quote!(_ => {})
+  };
+
+  let add_namespaces: TokenStream = root_attrs
+    .namespaces
     .iter()
     .map(|(prefix, namespace)| {
-      Some(quote!(
+      quote!(
         .ns(#prefix, #namespace)
-      ))
+      )
     })
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
     .fold(TokenStream::new(), |mut tokens, token| {
       tokens.append_all(token);
       tokens
     });
 
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
   quote! {
     use xml::writer::XmlEvent;
 
-    impl YaSerialize for #name {
+    impl #impl_generics YaSerialize for #name #ty_generics #where_clause {
       #[allow(unused_variables)]
       fn serialize<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
-        -> Result<(), String> {
-        if let Some(label) = writer.get_start_event_name() {
-          let struct_start_event = XmlEvent::start_element(label.as_ref());
-          let _ret = writer.write(struct_start_event);
-          return Ok(());
-        }
-        error!("Enum: start to expand {:?}", #root);
-
-        if !writer.skip_start_end() {
-          let struct_start_event = XmlEvent::start_element(#root)#add_namespaces;
-          let _ret = writer.write(struct_start_event);
+        -> Result<(), yaserde::Error> {
+        debug!("Enum: start to expand {:?}", #root);
+        let skip = writer.skip_start_end();
+        if !skip {
+          let label = writer.get_start_event_name().unwrap_or_else(|| #root.to_string());
+          let struct_start_event = XmlEvent::start_element(label.as_ref())#add_namespaces;
+          yaserde::YaSerialize::serialize_attributes(self, writer)?;
+          writer.write_start_element(struct_start_event)?;
         }
+        writer.set_start_event_name(None);
+        writer.set_skip_start_end(false);
 
-        match self {
+        match *self {
           #write_enum_content
         }
 
-        if !writer.skip_start_end() {
+        if !skip {
           let struct_end_event = XmlEvent::end_element();
-          let _ret = writer.write(struct_end_event);
+          writer.write(struct_end_event)?;
+        }
+        Ok(())
+      }
+
+      #[allow(unused_variables)]
+      fn serialize_attributes<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+        -> Result<(), yaserde::Error> {
+        match *self {
+          #write_enum_attributes
+          #other_variants
         }
-        writer.set_skip_start_end(false);
         Ok(())
       }
     }
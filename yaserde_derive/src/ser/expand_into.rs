@@ -0,0 +1,26 @@
+use attribute::parse_type;
+use proc_macro2::TokenStream;
+use syn::Generics;
+use syn::Ident;
+
+/// Serialize a clone of the container converted into `into`.
+pub fn serialize(name: &Ident, generics: &Generics, into: &str) -> TokenStream {
+  let into = parse_type("into", into);
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  quote! {
+    impl #impl_generics YaSerialize for #name #ty_generics #where_clause {
+      fn serialize<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+        -> Result<(), yaserde::Error> {
+        let value: #into = ::std::convert::Into::into(::std::clone::Clone::clone(self));
+        yaserde::YaSerialize::serialize(&value, writer)
+      }
+
+      fn serialize_attributes<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+        -> Result<(), yaserde::Error> {
+        let value: #into = ::std::convert::Into::into(::std::clone::Clone::clone(self));
+        yaserde::YaSerialize::serialize_attributes(&value, writer)
+      }
+    }
+  }
+}
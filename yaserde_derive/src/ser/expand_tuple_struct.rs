@@ -0,0 +1,121 @@
+use attribute::*;
+use field_type::*;
+use proc_macro2::TokenStream;
+use quote::TokenStreamExt;
+use std::collections::BTreeMap;
+use syn::DataStruct;
+use syn::Fields;
+use syn::Generics;
+use syn::Ident;
+use syn::Index;
+
+use ser::element::*;
+
+/// Whether tuple fields are those of a newtype, written transparently as its
+/// only field.
+pub fn is_newtype(fields: &Fields) -> bool {
+  match fields.iter().collect::<Vec<_>>()[..] {
+    [field] => {
+      let field_attrs = YaSerdeAttribute::parse(&field.attrs);
+      field_attrs.rename.is_none()
+        && !field_attrs.attribute
+        && matches!(get_field_type(field), FieldType::Value)
+    }
+    _ => false,
+  }
+}
+
+pub fn serialize(
+  data_struct: &DataStruct,
+  name: &Ident,
+  generics: &Generics,
+  root: &str,
+  namespaces: &BTreeMap<String, String>,
+) -> TokenStream {
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  if is_newtype(&data_struct.fields) {
+    let field_attrs = YaSerdeAttribute::parse(&data_struct.fields.iter().next().unwrap().attrs);
+    let serialize = serializer(&field_attrs);
+    // a value written by a `serialize_with` function carries no attributes
+    let serialize_attributes = match field_attrs.serialize_with {
+      Some(_) => quote!(Ok(())),
+      None => quote!(yaserde::YaSerialize::serialize_attributes(&self.0, writer)),
+    };
+    return quote! {
+      impl #impl_generics YaSerialize for #name #ty_generics #where_clause {
+        fn serialize<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+          -> Result<(), yaserde::Error> {
+          if !writer.skip_start_end() && writer.get_start_event_name().is_none() {
+            writer.set_start_event_name(Some(#root.to_string()));
+          }
+          #serialize(&self.0, writer)
+        }
+
+        #[allow(unused_variables)]
+        fn serialize_attributes<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+          -> Result<(), yaserde::Error> {
+          #serialize_attributes
+        }
+      }
+    };
+  }
+
+  let build_attributes = serialize_attributes(&data_struct.fields, |index, _| {
+    let index = Index::from(index);
+    quote!(&self.#index)
+  });
+
+  let add_namespaces: TokenStream = namespaces
+    .iter()
+    .map(|(prefix, namespace)| {
+      quote!(
+        .ns(#prefix, #namespace)
+      )
+    })
+    .fold(TokenStream::new(), |mut tokens, token| {
+      tokens.append_all(token);
+      tokens
+    });
+
+  let struct_inspector = serialize_elements(&data_struct.fields, |index, _| {
+    let index = Index::from(index);
+    quote!(&self.#index)
+  });
+
+  quote! {
+    use xml::writer::XmlEvent;
+
+    impl #impl_generics YaSerialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn serialize<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+        -> Result<(), yaserde::Error> {
+        debug!("Tuple struct: start to expand {:?}", #root);
+        let skip = writer.skip_start_end();
+        if !skip {
+          let label = writer.get_start_event_name().unwrap_or_else(|| #root.to_string());
+          let struct_start_event = XmlEvent::start_element(label.as_ref())#add_namespaces;
+          yaserde::YaSerialize::serialize_attributes(self, writer)?;
+          writer.write_start_element(struct_start_event)?;
+        }
+        writer.set_start_event_name(None);
+        writer.set_skip_start_end(false);
+
+        #struct_inspector
+
+        if !skip {
+          let struct_end_event = XmlEvent::end_element();
+          writer.write(struct_end_event)?;
+        }
+        Ok(())
+      }
+
+      #[allow(unused_variables)]
+      fn serialize_attributes<W: Write>(&self, writer: &mut yaserde::ser::Serializer<W>)
+        -> Result<(), yaserde::Error> {
+        #build_attributes
+        Ok(())
+      }
+    }
+  }
+}
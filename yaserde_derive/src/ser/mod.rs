@@ -1,45 +1,66 @@
-
 pub mod element;
 pub mod expand_enum;
+pub mod expand_into;
 pub mod expand_struct;
+pub mod expand_tuple_struct;
 
 use attribute;
-use proc_macro2::{Span, TokenStream};
+use bound;
+use case;
+use proc_macro2::TokenStream;
 use syn;
-use syn::Ident;
 
 pub fn expand_derive_serialize(ast: &syn::DeriveInput) -> Result<TokenStream, String> {
+  let root_attrs = attribute::YaSerdeAttribute::parse(&ast.attrs);
+  let ast = case::apply_rename_all(ast, &root_attrs.rename_all)?;
   let name = &ast.ident;
-  let attrs = &ast.attrs;
   let data = &ast.data;
 
-  let root_attrs = attribute::YaSerdeAttribute::parse(attrs);
   let root = root_attrs.clone().root.unwrap_or_else(|| name.to_string());
 
-  let root = if let Some(prefix) = root_attrs.prefix {
-    prefix + ":" + &root
+  let root = if let Some(ref prefix) = root_attrs.prefix {
+    format!("{}:{}", prefix, root)
   } else {
     root
   };
 
-  let impl_block = match *data {
-    syn::Data::Struct(ref data_struct) => {
-      expand_struct::serialize(data_struct, name, &root, &root_attrs.namespaces)
-    }
-    syn::Data::Enum(ref data_enum) => {
-      expand_enum::serialize(data_enum, name, &root, &root_attrs.namespaces)
-    }
-    syn::Data::Union(ref _data_union) => unimplemented!(),
+  let types = match root_attrs.into {
+    Some(ref into) => vec![attribute::parse_type("into", into)],
+    None => bound::field_types(data, |field_attrs| {
+      !field_attrs.skip_serializing && field_attrs.serialize_with.is_none()
+    }),
   };
-
-  let dummy_const = Ident::new(
-    &format!("_IMPL_YA_SERIALIZE_FOR_{}", name),
-    Span::call_site(),
+  let generics = bound::with_bound(
+    &ast.generics,
+    &types,
+    &root_attrs.serialize_bound,
+    &quote!(YaSerialize),
   );
 
+  let impl_block = if let Some(ref into) = root_attrs.into {
+    expand_into::serialize(name, &generics, into)
+  } else {
+    match *data {
+      syn::Data::Struct(ref data_struct) => match data_struct.fields {
+        syn::Fields::Unnamed(_) => expand_tuple_struct::serialize(
+          data_struct,
+          name,
+          &generics,
+          &root,
+          &root_attrs.namespaces,
+        ),
+        _ => expand_struct::serialize(data_struct, name, &generics, &root, &root_attrs.namespaces),
+      },
+      syn::Data::Enum(ref data_enum) => {
+        expand_enum::serialize(data_enum, name, &generics, &root, &root_attrs)
+      }
+      syn::Data::Union(ref _data_union) => unimplemented!(),
+    }
+  };
+
   let generated = quote! {
     #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
-    const #dummy_const: () = {
+    const _: () = {
       extern crate yaserde as _yaserde;
       #impl_block
     };